@@ -0,0 +1,241 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_io::Timer;
+use futures::{pin_mut, Stream, StreamExt};
+use serde_json::json;
+
+use crate::{
+    ChimeSource, Connection, Error, IdleReason, MediaStatus, Metadata, MixMode, PlayerState,
+    Preemption, Priority, ResumePolicy, SayOptions, Session, Theme, TtsProvider,
+};
+
+pub(crate) const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Completed,
+    Preempted,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Clone)]
+pub struct Announcement {
+    message: String,
+    options: SayOptions,
+    provider: Option<Arc<dyn TtsProvider>>,
+}
+
+impl Announcement {
+    pub fn new<T: Into<String>>(message: T) -> Self {
+        Announcement {
+            message: message.into(),
+            options: SayOptions::default(),
+            provider: None,
+        }
+    }
+
+    pub fn lang<T: Into<String>>(mut self, language: T) -> Self {
+        self.options.lang = Some(language.into());
+        self
+    }
+
+    pub fn slow(mut self, slow: bool) -> Self {
+        self.options.slow = slow;
+        self
+    }
+
+    pub fn ssml(mut self, ssml: bool) -> Self {
+        self.options.ssml = ssml;
+        self
+    }
+
+    pub fn volume(mut self, level: f32) -> Self {
+        self.options.volume = Some(level.clamp(0., 1.));
+        self
+    }
+
+    pub fn resume(mut self, policy: ResumePolicy) -> Self {
+        self.options.resume = Some(policy);
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.options.priority = priority;
+        self
+    }
+
+    pub fn preemption(mut self, mode: Preemption) -> Self {
+        self.options.preemption = mode;
+        self
+    }
+
+    pub fn chime(mut self, source: ChimeSource) -> Self {
+        self.options.chime = Some(source);
+        self
+    }
+
+    pub fn metadata(mut self, metadata: Metadata) -> Self {
+        self.options.metadata = Some(metadata);
+        self
+    }
+
+    pub fn mix(mut self, mode: MixMode) -> Self {
+        self.options.mix = mode;
+        self
+    }
+
+    pub fn voice<T: Into<String>>(mut self, voice: T) -> Self {
+        self.options.voice = Some(voice.into());
+        self
+    }
+
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        theme.apply(&mut self.options);
+        self
+    }
+
+    pub fn provider(mut self, provider: Arc<dyn TtsProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn options(&self) -> &SayOptions {
+        &self.options
+    }
+
+    pub async fn send(self, connection: &Connection) -> Result<AnnouncementHandle<'_>, Error> {
+        let provider = self.provider.unwrap_or_else(|| connection.provider.clone());
+        connection
+            .announce(self.message, self.options, provider)
+            .await
+    }
+
+    pub async fn send_and_wait(self, connection: &Connection) -> Result<(), Error> {
+        let provider = self.provider.unwrap_or_else(|| connection.provider.clone());
+        connection
+            .speak(self.message, self.options, provider, true)
+            .await
+    }
+}
+
+impl Connection {
+    pub async fn announce_stream<S, T>(&self, messages: S) -> Result<(), Error>
+    where
+        S: Stream<Item = T>,
+        T: Into<String>,
+    {
+        pin_mut!(messages);
+        while let Some(message) = messages.next().await {
+            match self
+                .speak(
+                    message.into(),
+                    SayOptions::default(),
+                    self.provider.clone(),
+                    true,
+                )
+                .await
+            {
+                Err(Error::Suppressed(_)) => {}
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Ticket {
+    session: Mutex<Option<Session>>,
+}
+
+impl Ticket {
+    pub(crate) fn loaded(&self, session: Session) {
+        *self.session.lock().unwrap() = Some(session);
+    }
+
+    pub(crate) fn media(&self) -> Option<(String, i32)> {
+        let session = self.session.lock().unwrap().clone()?;
+        Some((session.transport_id, session.media_session_id?))
+    }
+}
+
+/// Returned once every chunk of an announcement has been loaded; dropping it leaves the clip
+/// playing.
+pub struct AnnouncementHandle<'a> {
+    connection: &'a Connection,
+    media: Option<(String, i32)>,
+}
+
+impl<'a> AnnouncementHandle<'a> {
+    pub(crate) fn new(connection: &'a Connection, ticket: &Ticket) -> Self {
+        AnnouncementHandle {
+            connection,
+            media: ticket.media(),
+        }
+    }
+
+    pub fn media_session_id(&self) -> Option<i32> {
+        self.media.as_ref().map(|(_, id)| *id)
+    }
+
+    pub async fn done(self) -> Result<Outcome, Error> {
+        loop {
+            if let Some(outcome) = self.poll().await? {
+                return Ok(outcome);
+            }
+            Timer::after(STATUS_POLL_INTERVAL).await;
+        }
+    }
+
+    pub async fn cancel(self) -> Result<Outcome, Error> {
+        if let Some(outcome) = self.poll().await? {
+            return Ok(outcome);
+        }
+        if let Some((transport_id, id)) = &self.media {
+            self.connection
+                .run(move |device| async move {
+                    device
+                        .control(transport_id, *id, json!({ "type": "STOP" }))
+                        .await?;
+                    Ok(())
+                })
+                .await?;
+        }
+        Ok(Outcome::Cancelled)
+    }
+
+    async fn poll(&self) -> Result<Option<Outcome>, Error> {
+        let (transport_id, id) = match &self.media {
+            Some((transport_id, id)) => (transport_id, *id),
+            None => return Ok(Some(Outcome::Completed)),
+        };
+        let statuses = self
+            .connection
+            .run(move |device| async move { device.media_status(transport_id, Some(id)).await })
+            .await?;
+        Ok(settled(statuses, id))
+    }
+}
+
+pub(crate) fn settled(statuses: Vec<MediaStatus>, id: i32) -> Option<Outcome> {
+    match statuses
+        .into_iter()
+        .find(|status| status.media_session_id == id)
+    {
+        Some(entry) if !matches!(entry.player_state, PlayerState::Idle) => None,
+        Some(entry) => Some(match entry.idle_reason {
+            Some(IdleReason::Interrupted) | Some(IdleReason::Cancelled) => Outcome::Preempted,
+            Some(IdleReason::Error) => Outcome::Failed,
+            _ => Outcome::Completed,
+        }),
+        None => Some(Outcome::Completed),
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_io::{Async, Timer};
+use futures::{
+    future::{select, Either},
+    io::BufReader,
+    pin_mut, AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, StreamExt,
+};
+use serde_json::json;
+
+use crate::{request, Connection, Error, Pool, Registry};
+
+const MAX_BODY: usize = 64 * 1024;
+const DISCOVERY_WINDOW: Duration = Duration::from_secs(5);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Api {
+    registry: Arc<Registry>,
+    pool: Pool,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: &'static str, body: serde_json::Value) -> Self {
+        Response {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn text(status: &'static str, body: String) -> Self {
+        Response {
+            status,
+            content_type: "text/plain; version=0.0.4",
+            body,
+        }
+    }
+
+    fn error(status: &'static str, message: impl ToString) -> Self {
+        Self::json(status, json!({ "error": message.to_string() }))
+    }
+}
+
+impl Api {
+    pub fn new(registry: Registry) -> Self {
+        Self::with_registry(Arc::new(registry))
+    }
+
+    pub fn with_registry(registry: Arc<Registry>) -> Self {
+        Api {
+            registry,
+            pool: Pool::new(),
+        }
+    }
+
+    pub fn pool(mut self, pool: Pool) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    pub async fn serve(&self, addr: SocketAddr) -> Result<(), Error> {
+        let listener = Async::<TcpListener>::bind(addr)?;
+        listener
+            .incoming()
+            .for_each_concurrent(None, |stream| async move {
+                match stream {
+                    Ok(stream) => {
+                        let _ = self.handle(stream).await;
+                    }
+                    Err(_e) => {
+                        log!(warn, error = %_e, "failed to accept api connection");
+                    }
+                }
+            })
+            .await;
+        Ok(())
+    }
+
+    async fn handle(&self, stream: Async<TcpStream>) -> Result<(), Error> {
+        let mut reader = BufReader::new(stream);
+        let response = {
+            let request = read_request(&mut reader);
+            pin_mut!(request);
+            match select(request, Timer::after(READ_TIMEOUT)).await {
+                Either::Left((Ok(request), _)) => {
+                    self.route(&request.method, &request.path, &request.body)
+                        .await
+                }
+                Either::Left((Err(response), _)) => response,
+                Either::Right(_) => {
+                    Response::error("408 Request Timeout", "request was not received in time")
+                }
+            }
+        };
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.0 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.status,
+                    response.content_type,
+                    response.body.len(),
+                    response.body
+                )
+                .as_bytes(),
+            )
+            .await?;
+        stream.flush().await?;
+        Ok(())
+    }
+
+    async fn route(&self, method: &str, path: &str, body: &[u8]) -> Response {
+        let path = path.split('?').next().unwrap_or(path);
+        let segments: Vec<_> = path.trim_matches('/').split('/').map(decode).collect();
+        let segments: Vec<_> = segments.iter().map(String::as_str).collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["devices"]) => Response::json("200 OK", self.registry.to_json()),
+            #[cfg(feature = "metrics")]
+            ("GET", ["metrics"]) => Response::text("200 OK", crate::Metrics::global().render()),
+            ("GET", ["devices", name, "status"]) => match self.status(name).await {
+                Ok(status) => Response::json("200 OK", status),
+                Err(e) => failure(e),
+            },
+            ("POST", ["devices", name, "say"]) => {
+                let (message, options) = request::parse(body);
+                if message.is_empty() {
+                    return Response::error("400 Bad Request", "message is empty");
+                }
+                let result = match self.connection(name).await {
+                    Ok(connection) => connection
+                        .say_with_options(message, options)
+                        .await
+                        .map(|handle| handle.media_session_id()),
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(id) => Response::json("202 Accepted", json!({ "media_session_id": id })),
+                    Err(e) => {
+                        self.evict(name, &e);
+                        failure(e)
+                    }
+                }
+            }
+            (_, ["devices"]) | (_, ["devices", _, "status"]) | (_, ["devices", _, "say"]) => {
+                Response::error("405 Method Not Allowed", "method not allowed")
+            }
+            _ => Response::error("404 Not Found", "not found"),
+        }
+    }
+
+    async fn status(&self, name: &str) -> Result<serde_json::Value, Error> {
+        let connection = self.connection(name).await?;
+        let status = connection.status().await.map_err(|e| {
+            self.evict(name, &e);
+            e
+        })?;
+        Ok(request::summary(status))
+    }
+
+    async fn connection(&self, name: &str) -> Result<Arc<Connection>, Error> {
+        if self.registry.cached(name).is_none() {
+            self.registry.refresh(DISCOVERY_WINDOW).await?;
+        }
+        let target = self
+            .registry
+            .cached(name)
+            .ok_or_else(|| Error::DeviceNotFound(name.to_string()))?
+            .target();
+        let connection = self.pool.get(&target).await?;
+        if connection.health().connected {
+            return Ok(connection);
+        }
+        self.pool.evict(&target);
+        self.pool.get(&target).await
+    }
+
+    fn evict(&self, name: &str, error: &Error) {
+        if let (true, Some(device)) = (error.is_connection_error(), self.registry.cached(name)) {
+            self.pool.evict(&device.target());
+        }
+    }
+}
+
+fn failure(error: Error) -> Response {
+    match error {
+        Error::DeviceNotFound(_) => Response::error("404 Not Found", error),
+        Error::Suppressed(_) => Response::error("409 Conflict", error),
+        error => Response::error("502 Bad Gateway", error),
+    }
+}
+
+async fn read_request<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Request, Response> {
+    let malformed = |_| Response::error("400 Bad Request", "malformed request");
+    let mut line = String::new();
+    reader.read_line(&mut line).await.map_err(malformed)?;
+    let mut length = 0;
+    let mut header = String::new();
+    while reader.read_line(&mut header).await.map_err(malformed)? > 2 {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        header.clear();
+    }
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_owned(), path.to_owned()),
+        _ => return Err(Response::error("400 Bad Request", "malformed request line")),
+    };
+    if length > MAX_BODY {
+        return Err(Response::error(
+            "413 Payload Too Large",
+            "request body is too large",
+        ));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.map_err(malformed)?;
+    Ok(Request { method, path, body })
+}
+
+/// Decodes percent escapes in a path segment. `+` is left alone, since it only means a space
+/// in form-encoded query strings.
+fn decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use async_io::block_on;
+    use futures::io::Cursor;
+
+    use super::*;
+
+    fn read(raw: &str) -> Result<Request, Response> {
+        block_on(read_request(&mut Cursor::new(raw.as_bytes().to_vec())))
+    }
+
+    #[test]
+    fn reads_the_request_line_and_body() {
+        let request = read(
+            "POST /devices/Kitchen/say HTTP/1.1\r\nHost: x\r\nContent-Length: 13\r\n\r\n{\"message\":1}",
+        )
+        .unwrap_or_else(|response| panic!("{}", response.status));
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/devices/Kitchen/say");
+        assert_eq!(request.body, b"{\"message\":1}");
+    }
+
+    #[test]
+    fn ignores_the_case_of_header_names() {
+        let request = read("POST / HTTP/1.1\r\ncontent-LENGTH:  2\r\n\r\nhi")
+            .unwrap_or_else(|response| panic!("{}", response.status));
+        assert_eq!(request.body, b"hi");
+    }
+
+    #[test]
+    fn rejects_oversized_and_malformed_requests() {
+        let oversized = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        assert!(matches!(read(&oversized), Err(response) if response.status.starts_with("413")));
+        assert!(
+            matches!(read("GARBAGE\r\n\r\n"), Err(response) if response.status.starts_with("400"))
+        );
+        assert!(matches!(read(""), Err(response) if response.status.starts_with("400")));
+        let truncated = "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort";
+        assert!(matches!(read(truncated), Err(response) if response.status.starts_with("400")));
+    }
+
+    #[test]
+    fn decodes_percent_escapes_but_not_plus() {
+        assert_eq!(decode("A+B"), "A+B");
+        assert_eq!(decode("Living%20Room"), "Living Room");
+        assert_eq!(decode("A%2bB"), "A+B");
+        assert_eq!(decode("caf%C3%A9"), "café");
+        assert_eq!(decode("100%"), "100%");
+        assert_eq!(decode("%zz"), "%zz");
+    }
+}
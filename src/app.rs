@@ -0,0 +1,47 @@
+use serde_json::Value;
+
+use crate::{AppStatus, Connection, Error};
+
+impl Connection {
+    pub async fn launch_app(&self, app_id: &str) -> Result<AppStatus, Error> {
+        let id = &self.link.id;
+        let status = self
+            .run_once(
+                "launch",
+                self.settings.timeouts.command,
+                move |device| async move {
+                    let app = device
+                        .launch(app_id)
+                        .await
+                        .map_err(|source| Error::LaunchApp {
+                            device: id.clone(),
+                            source: Box::new(source),
+                        })?;
+                    device.connect(&app.transport_id).await?;
+                    Ok(app)
+                },
+            )
+            .await?;
+        *self.app.lock().unwrap() = Some(status.clone());
+        Ok(status)
+    }
+
+    pub async fn send_message(&self, namespace: &str, payload: Value) -> Result<(), Error> {
+        let transport_id = match &*self.app.lock().unwrap() {
+            Some(app) => app.transport_id.clone(),
+            None => return Err(Error::NoActiveApp),
+        };
+        let (transport_id, payload) = (&transport_id, &payload);
+        self.run_once("message", self.settings.timeouts.command, move |device| async move {
+            device.send(transport_id, namespace, payload.clone()).await
+        })
+        .await
+    }
+
+    pub async fn stop_app(&self) -> Result<(), Error> {
+        let app = self.app.lock().unwrap().take().ok_or(Error::NoActiveApp)?;
+        let session_id = &app.session_id;
+        self.run(move |device| async move { device.stop_app(session_id).await })
+            .await
+    }
+}
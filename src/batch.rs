@@ -0,0 +1,185 @@
+use std::sync::{Arc, Mutex};
+
+use futures::channel::oneshot;
+
+use crate::Error;
+
+type Outcome = Result<(), Arc<Error>>;
+type Sender = oneshot::Sender<Outcome>;
+
+struct Pending {
+    language: String,
+    messages: Vec<(String, Sender)>,
+}
+
+#[derive(Default)]
+pub(crate) struct Batch {
+    pending: Mutex<Option<Pending>>,
+}
+
+pub(crate) enum Slot<'a> {
+    Alone(String),
+    Lead(Lead<'a>, String),
+    Follow(oneshot::Receiver<Outcome>),
+}
+
+pub(crate) struct Lead<'a> {
+    batch: &'a Batch,
+    done: bool,
+}
+
+impl Batch {
+    pub(crate) fn enter(&self, language: &str, message: String) -> Slot<'_> {
+        let mut pending = self.pending.lock().unwrap();
+        match &mut *pending {
+            Some(pending) if pending.language == language => {
+                let (sender, receiver) = oneshot::channel();
+                pending.messages.push((message, sender));
+                Slot::Follow(receiver)
+            }
+            Some(_) => Slot::Alone(message),
+            None => {
+                *pending = Some(Pending {
+                    language: language.to_string(),
+                    messages: Vec::new(),
+                });
+                Slot::Lead(
+                    Lead {
+                        batch: self,
+                        done: false,
+                    },
+                    message,
+                )
+            }
+        }
+    }
+}
+
+impl Lead<'_> {
+    pub(crate) fn finish(mut self) -> (Vec<String>, Vec<Sender>) {
+        self.done = true;
+        let pending = self.batch.pending.lock().unwrap().take();
+        pending
+            .map(|pending| pending.messages.into_iter().unzip())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Lead<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.batch.pending.lock().unwrap().take();
+        }
+    }
+}
+
+pub(crate) fn join<I: IntoIterator<Item = String>>(messages: I) -> String {
+    let mut out = String::new();
+    for message in messages {
+        let message = message.trim();
+        if message.is_empty() {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(message);
+        if !message.ends_with(['.', '!', '?']) {
+            out.push('.');
+        }
+    }
+    out
+}
+
+pub(crate) fn notify(followers: Vec<Sender>, result: Result<(), Error>) -> Result<(), Error> {
+    if followers.is_empty() {
+        return result;
+    }
+    let result = result.map_err(Arc::new);
+    for follower in followers {
+        let _ = follower.send(result.clone());
+    }
+    result.map_err(Error::Batched)
+}
+
+pub(crate) async fn follow(receiver: oneshot::Receiver<Outcome>) -> Result<(), Error> {
+    match receiver.await {
+        Ok(result) => result.map_err(Error::Batched),
+        Err(_) => Err(Error::Cancelled),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_io::block_on;
+
+    use super::*;
+
+    fn strings(messages: &[&str]) -> Vec<String> {
+        messages.iter().map(|message| message.to_string()).collect()
+    }
+
+    #[test]
+    fn join_adds_missing_sentence_ends() {
+        assert_eq!(
+            join(strings(&["Door open", "Window closed!", "Anyone home?"])),
+            "Door open. Window closed! Anyone home?"
+        );
+    }
+
+    #[test]
+    fn join_trims_and_skips_empty_messages() {
+        assert_eq!(
+            join(strings(&["  Door open. ", "", "   ", "Bye"])),
+            "Door open. Bye."
+        );
+        assert_eq!(join(Vec::new()), "");
+    }
+
+    #[test]
+    fn enter_batches_by_language() {
+        let batch = Batch::default();
+        let lead = match batch.enter("en", "first".to_owned()) {
+            Slot::Lead(lead, first) => {
+                assert_eq!(first, "first");
+                lead
+            }
+            _ => panic!("first message should lead"),
+        };
+        assert!(matches!(
+            batch.enter("en", "second".to_owned()),
+            Slot::Follow(_)
+        ));
+        assert!(matches!(
+            batch.enter("de", "dritte".to_owned()),
+            Slot::Alone(_)
+        ));
+        let (messages, followers) = lead.finish();
+        assert_eq!(messages, strings(&["second"]));
+        assert_eq!(followers.len(), 1);
+        assert!(matches!(
+            batch.enter("en", "next".to_owned()),
+            Slot::Lead(..)
+        ));
+    }
+
+    #[test]
+    fn followers_share_the_leaders_error() {
+        let (sender, receiver) = oneshot::channel();
+        let result = notify(vec![sender], Err(Error::NoActiveMedia));
+        assert!(
+            matches!(result, Err(Error::Batched(ref e)) if matches!(**e, Error::NoActiveMedia))
+        );
+        let followed = block_on(follow(receiver));
+        assert!(
+            matches!(followed, Err(Error::Batched(ref e)) if matches!(**e, Error::NoActiveMedia))
+        );
+    }
+
+    #[test]
+    fn followers_are_cancelled_when_the_leader_goes_away() {
+        let (sender, receiver) = oneshot::channel();
+        drop(sender);
+        assert!(matches!(block_on(follow(receiver)), Err(Error::Cancelled)));
+    }
+}
@@ -0,0 +1,103 @@
+use std::{process, time::Duration};
+
+use async_io::Timer;
+use cast_notify::{discover_for, DiscoverOptions, DiscoveryFilter, Error, SayOptions, Target};
+use futures::{executor::block_on, pin_mut, StreamExt, TryStreamExt};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "cast-notify",
+    about = "Speak announcements on Chromecast devices"
+)]
+struct Args {
+    #[structopt(long, default_value = "3")]
+    timeout: u64,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    List,
+    Say {
+        #[structopt(long, short)]
+        device: String,
+        #[structopt(long, short)]
+        lang: Option<String>,
+        #[structopt(long)]
+        slow: bool,
+        #[structopt(long)]
+        voice: Option<String>,
+        message: String,
+    },
+    Volume {
+        #[structopt(long, short)]
+        device: String,
+        level: Option<f32>,
+    },
+}
+
+fn main() {
+    let args = Args::from_args();
+    if let Err(e) = block_on(run(args)) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<(), Error> {
+    let timeout = Duration::from_secs(args.timeout);
+    match args.command {
+        Command::List => {
+            let targets: Vec<Target> = discover_for(timeout).try_collect().await?;
+            for target in targets {
+                println!(
+                    "{}\t{}\t{}",
+                    target.name(),
+                    target.addr(),
+                    target.model().unwrap_or("-")
+                );
+            }
+        }
+        Command::Say {
+            device,
+            lang,
+            slow,
+            voice,
+            message,
+        } => {
+            let connection = find(&device, timeout).await?.connect().await?;
+            let options = SayOptions {
+                lang,
+                slow,
+                voice,
+                ..SayOptions::default()
+            };
+            connection.say_with_options(message, options).await?;
+        }
+        Command::Volume { device, level } => {
+            let connection = find(&device, timeout).await?.connect().await?;
+            match level {
+                Some(level) => connection.set_volume(level).await?,
+                None => println!("{:.2}", connection.volume().await?),
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn find(device: &str, timeout: Duration) -> Result<Target, Error> {
+    let targets = DiscoverOptions::new()
+        .filter(DiscoveryFilter::new().name(device))
+        .discover()
+        .take_until(Timer::after(timeout));
+    pin_mut!(targets);
+    match targets.next().await {
+        Some(target) => target,
+        None => {
+            eprintln!("error: no device named {:?} found", device);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Synchronous wrappers that drive the async API on the calling thread.
+//!
+//! These must not be called from inside an async runtime: parking a runtime worker on a
+//! connection that runtime is meant to be driving deadlocks. With the `tokio` feature the
+//! wrappers detect a running tokio runtime and fail with [`Error::InsideRuntime`] instead;
+//! other runtimes cannot be detected, so use the async API there.
+
+use std::{borrow::Cow, future::Future, time::Duration};
+
+use futures::{executor::block_on_stream, TryFutureExt};
+
+use crate::{ConnectOptions, DeviceStatus, Error, MediaStatus, SayOptions, Target};
+
+pub fn discover() -> impl Iterator<Item = Result<Target, Error>> {
+    block_on_stream(Box::pin(crate::discover()))
+}
+
+pub fn discover_for(duration: Duration) -> impl Iterator<Item = Result<Target, Error>> {
+    block_on_stream(Box::pin(crate::discover_for(duration)))
+}
+
+pub fn discover_named<T: Into<String>>(pattern: T) -> impl Iterator<Item = Result<Target, Error>> {
+    block_on_stream(Box::pin(crate::discover_named(pattern)))
+}
+
+pub struct Connection {
+    inner: crate::Connection,
+}
+
+impl Connection {
+    pub fn connect(target: Target) -> Result<Self, Error> {
+        Self::connect_with(target, ConnectOptions::default())
+    }
+
+    pub fn connect_with(target: Target, options: ConnectOptions) -> Result<Self, Error> {
+        Ok(Connection {
+            inner: block_on(target.connect_with(options))?,
+        })
+    }
+
+    pub fn say<'a, T: Into<Cow<'a, str>>>(&self, message: T) -> Result<(), Error> {
+        block_on(self.inner.say(message).map_ok(drop))
+    }
+
+    pub fn say_and_wait<'a, T: Into<Cow<'a, str>>>(&self, message: T) -> Result<(), Error> {
+        block_on(self.inner.say_and_wait(message))
+    }
+
+    pub fn say_with_options<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        options: SayOptions,
+    ) -> Result<(), Error> {
+        block_on(self.inner.say_with_options(message, options).map_ok(drop))
+    }
+
+    pub fn display<T: Into<String>>(&self, message: T) -> Result<(), Error> {
+        block_on(self.inner.display(message))
+    }
+
+    pub fn volume(&self) -> Result<f32, Error> {
+        block_on(self.inner.volume())
+    }
+
+    pub fn set_volume(&self, level: f32) -> Result<(), Error> {
+        block_on(self.inner.set_volume(level))
+    }
+
+    pub fn status(&self) -> Result<DeviceStatus, Error> {
+        block_on(self.inner.status())
+    }
+
+    pub fn media_status(&self) -> Result<Option<MediaStatus>, Error> {
+        block_on(self.inner.media_status())
+    }
+
+    pub fn stop(&self) -> Result<(), Error> {
+        block_on(self.inner.stop())
+    }
+
+    pub fn disconnect(self) -> Result<(), Error> {
+        block_on(self.inner.disconnect())
+    }
+
+    pub fn inner(&self) -> &crate::Connection {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut crate::Connection {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> crate::Connection {
+        self.inner
+    }
+}
+
+fn block_on<T, F: Future<Output = Result<T, Error>>>(future: F) -> Result<T, Error> {
+    #[cfg(feature = "tokio")]
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err(Error::InsideRuntime);
+    }
+    futures::executor::block_on(future)
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+
+use crate::{Connection, Error};
+
+#[derive(Debug, Default)]
+pub struct BroadcastOutcome {
+    results: Vec<(String, Result<(), Error>)>,
+}
+
+impl BroadcastOutcome {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<T: Into<String>>(&mut self, device: T, result: Result<(), Error>) {
+        self.results.push((device.into(), result));
+    }
+
+    pub(crate) fn record(&mut self, connection: &Connection, result: Result<(), Error>) {
+        self.push(connection.device_id().to_string(), result);
+    }
+
+    pub(crate) fn fail(&mut self, error: Error) {
+        let device = match (&error, error.device()) {
+            (_, Some(device)) => device.to_string(),
+            (Error::DeviceNotFound(name), None) => name.clone(),
+            _ => "unknown device".to_string(),
+        };
+        self.push(device, Err(error));
+    }
+
+    pub fn all_ok(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    pub fn successes(&self) -> impl Iterator<Item = &str> {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(device, _)| device.as_str())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &Error)> {
+        self.results
+            .iter()
+            .filter_map(|(device, result)| Some((device.as_str(), result.as_ref().err()?)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Result<(), Error>)> {
+        self.results
+            .iter()
+            .map(|(device, result)| (device.as_str(), result))
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    pub fn into_results(self) -> Vec<(String, Result<(), Error>)> {
+        self.results
+    }
+}
+
+impl fmt::Display for BroadcastOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} devices succeeded",
+            self.successes().count(),
+            self.len()
+        )?;
+        for (device, error) in self.failures() {
+            write!(f, "; {}: {}", device, error)?;
+        }
+        Ok(())
+    }
+}
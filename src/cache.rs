@@ -0,0 +1,75 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io::Read,
+};
+
+use crate::{Clip, Error, LocalAudioServer, SayOptions, TtsProvider, Voice};
+
+pub struct TtsCache<P> {
+    provider: P,
+    server: LocalAudioServer,
+}
+
+impl<P: TtsProvider> TtsCache<P> {
+    pub fn new(provider: P, server: LocalAudioServer) -> Self {
+        TtsCache { provider, server }
+    }
+
+    pub fn prefetch(&self, message: &str, language: &str) -> Result<Clip, Error> {
+        self.synthesize(message, language, &SayOptions::default())
+    }
+
+    pub fn forget(&self, message: &str, language: &str, options: &SayOptions) {
+        if let Some(clip) = self.server.get(&path(message, language, options)) {
+            self.server.remove(&clip);
+        }
+    }
+}
+
+impl<P: TtsProvider> TtsProvider for TtsCache<P> {
+    fn synthesize(
+        &self,
+        message: &str,
+        language: &str,
+        options: &SayOptions,
+    ) -> Result<Clip, Error> {
+        let path = path(message, language, options);
+        if let Some(clip) = self.server.get(&path) {
+            return Ok(clip);
+        }
+        let clip = self.provider.synthesize(message, language, options)?;
+        let mut bytes = Vec::new();
+        ureq::get(&clip.url)
+            .call()
+            .map_err(Box::new)?
+            .into_reader()
+            .read_to_end(&mut bytes)?;
+        Ok(self.server.serve_at(&path, bytes, clip.content_type))
+    }
+
+    fn max_len(&self) -> Option<usize> {
+        self.provider.max_len()
+    }
+
+    fn supports_ssml(&self) -> bool {
+        self.provider.supports_ssml()
+    }
+
+    fn voices(&self) -> Vec<Voice> {
+        self.provider.voices()
+    }
+}
+
+fn path(message: &str, language: &str, options: &SayOptions) -> String {
+    let mut hasher = DefaultHasher::new();
+    (
+        message,
+        language,
+        options.slow,
+        options.ssml,
+        &options.voice,
+    )
+        .hash(&mut hasher);
+    format!("/cache/{:016x}", hasher.finish())
+}
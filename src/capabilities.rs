@@ -0,0 +1,47 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    const VIDEO_OUT: u32 = 1;
+    const VIDEO_IN: u32 = 2;
+    const AUDIO_OUT: u32 = 4;
+    const AUDIO_IN: u32 = 8;
+    const MULTIZONE_GROUP: u32 = 32;
+
+    pub fn from_bits(bits: u32) -> Self {
+        Capabilities(bits)
+    }
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_known(self) -> bool {
+        self.0 != 0
+    }
+
+    pub fn supports_video(self) -> bool {
+        !self.is_known() || self.video_out()
+    }
+
+    pub fn video_out(self) -> bool {
+        self.0 & Self::VIDEO_OUT != 0
+    }
+
+    pub fn video_in(self) -> bool {
+        self.0 & Self::VIDEO_IN != 0
+    }
+
+    pub fn audio_out(self) -> bool {
+        self.0 & Self::AUDIO_OUT != 0
+    }
+
+    pub fn audio_in(self) -> bool {
+        self.0 & Self::AUDIO_IN != 0
+    }
+
+    pub fn multizone_group(self) -> bool {
+        self.0 & Self::MULTIZONE_GROUP != 0
+    }
+}
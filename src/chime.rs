@@ -0,0 +1,100 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use crate::{runtime::unblock, Clip, Connection, Error, LocalAudioServer, Melody};
+
+const TONE_PATH: &str = "/chime/tone";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChimeSource {
+    Tone,
+    Melody(Melody),
+    Url(Clip),
+    File(PathBuf),
+}
+
+impl Connection {
+    pub fn set_audio_server(&mut self, server: LocalAudioServer) {
+        *self.server.get_mut().unwrap() = Some(server);
+    }
+
+    pub(crate) fn audio_server(&self) -> Result<LocalAudioServer, Error> {
+        let mut server = self.server.lock().unwrap();
+        if let Some(server) = &*server {
+            return Ok(server.clone());
+        }
+        let local = LocalAudioServer::reachable_from(self.link.id.addr)?;
+        *server = Some(local.clone());
+        Ok(local)
+    }
+
+    pub(crate) async fn chime_clip(&self, source: &ChimeSource) -> Result<Clip, Error> {
+        match source {
+            ChimeSource::Url(clip) => Ok(clip.clone()),
+            ChimeSource::Tone => {
+                let server = self.audio_server()?;
+                Ok(match server.get(TONE_PATH) {
+                    Some(clip) => clip,
+                    None => server.serve_at(TONE_PATH, Melody::chime().to_wav(), "audio/wav"),
+                })
+            }
+            ChimeSource::Melody(melody) => {
+                let server = self.audio_server()?;
+                let mut hasher = DefaultHasher::new();
+                melody.hash(&mut hasher);
+                let path = format!("{}/{:016x}", TONE_PATH, hasher.finish());
+                Ok(match server.get(&path) {
+                    Some(clip) => clip,
+                    None => server.serve_at(&path, melody.to_wav(), "audio/wav"),
+                })
+            }
+            ChimeSource::File(file) => {
+                let server = self.audio_server()?;
+                let mut hasher = DefaultHasher::new();
+                file.hash(&mut hasher);
+                let path = format!("/chime/{:016x}", hasher.finish());
+                let content_type = content_type(file);
+                let file = file.clone();
+                let bytes = unblock(move || std::fs::read(file)).await?;
+                Ok(server.serve_at(&path, bytes, content_type))
+            }
+        }
+    }
+}
+
+fn content_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("mp3") => "audio/mpeg",
+        Some(ext) if ext.eq_ignore_ascii_case("wav") => "audio/wav",
+        Some(ext) if ext.eq_ignore_ascii_case("ogg") => "audio/ogg",
+        Some(ext) if ext.eq_ignore_ascii_case("flac") => "audio/flac",
+        Some(ext) if ext.eq_ignore_ascii_case("aac") || ext.eq_ignore_ascii_case("m4a") => {
+            "audio/mp4"
+        }
+        _ => "application/octet-stream",
+    }
+}
+
+pub(crate) fn wav(sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
@@ -0,0 +1,95 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    time::Duration,
+};
+
+use async_io::Timer;
+use futures::{pin_mut, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Connection, ConnectionSettings, DiscoverOptions, DiscoveryFilter, Error, Policy, QuietHours,
+    Target, Zones,
+};
+
+const CAST_PORT: u16 = 8009;
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeviceAlias {
+    Socket(SocketAddr),
+    Ip(IpAddr),
+    Name(String),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub language: Option<String>,
+    pub volume: Option<f32>,
+    pub fallback: bool,
+    pub quiet_hours: Option<QuietHours>,
+    pub devices: HashMap<String, DeviceAlias>,
+    pub zones: Zones,
+}
+
+impl Config {
+    pub fn from_toml(source: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(source)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    pub fn policy(&self) -> Policy {
+        Policy {
+            quiet_hours: self.quiet_hours,
+            ..Policy::default()
+        }
+    }
+
+    pub fn settings(&self) -> ConnectionSettings {
+        ConnectionSettings {
+            language: self.language.clone(),
+            volume: self.volume,
+            fallback: self.fallback,
+            ..ConnectionSettings::default()
+        }
+    }
+
+    pub fn configure(&self, mut connection: Connection) -> Connection {
+        connection.configure(self.settings());
+        connection.with_policy(self.policy())
+    }
+
+    pub async fn resolve(&self, alias: &str) -> Result<Target, Error> {
+        let key = self
+            .devices
+            .keys()
+            .find(|key| key.eq_ignore_ascii_case(alias))
+            .map(String::as_str)
+            .unwrap_or(alias);
+        let pattern = match self.devices.get(key) {
+            Some(DeviceAlias::Socket(addr)) => return Ok(Target::new(key, *addr)),
+            Some(DeviceAlias::Ip(ip)) => {
+                return Ok(Target::new(key, SocketAddr::new(*ip, CAST_PORT)))
+            }
+            Some(DeviceAlias::Name(pattern)) => pattern.as_str(),
+            None => alias,
+        };
+        let targets = DiscoverOptions::new()
+            .filter(DiscoveryFilter::new().name(pattern))
+            .discover()
+            .take_until(Timer::after(RESOLVE_TIMEOUT));
+        pin_mut!(targets);
+        targets
+            .next()
+            .await
+            .unwrap_or_else(|| Err(Error::DeviceNotFound(alias.to_string())))
+    }
+}
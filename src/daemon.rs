@@ -0,0 +1,190 @@
+use std::{
+    fs, io,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_io::{Async, Timer};
+use futures::{
+    future::{select, Either},
+    io::BufReader,
+    pin_mut, AsyncBufReadExt, AsyncWriteExt, StreamExt,
+};
+use serde_json::{json, Value};
+
+use crate::{info::string, request, Connection, Error, Pool, Registry};
+
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const DISCOVERY_WINDOW: Duration = Duration::from_secs(5);
+
+enum Command {
+    List,
+    Say { device: String },
+    Volume { device: String, level: Option<f32> },
+    Status { device: String },
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Self, Error> {
+        let value: Value = serde_json::from_str(line)?;
+        let device = || string(&value, "device").ok_or(Error::InvalidRequest("device is missing"));
+        match value["command"].as_str() {
+            Some("list") => Ok(Command::List),
+            Some("say") => Ok(Command::Say { device: device()? }),
+            Some("volume") => {
+                let level = match &value["level"] {
+                    Value::Null => None,
+                    level => Some(
+                        level
+                            .as_f64()
+                            .ok_or(Error::InvalidRequest("level is not a number"))?
+                            as f32,
+                    ),
+                };
+                Ok(Command::Volume {
+                    device: device()?,
+                    level,
+                })
+            }
+            Some("status") => Ok(Command::Status { device: device()? }),
+            _ => Err(Error::InvalidRequest("unknown command")),
+        }
+    }
+}
+
+pub struct Daemon {
+    registry: Arc<Registry>,
+    pool: Pool,
+    refresh_interval: Duration,
+}
+
+impl Daemon {
+    pub fn new(registry: Registry) -> Self {
+        Self::with_registry(Arc::new(registry))
+    }
+
+    pub fn with_registry(registry: Arc<Registry>) -> Self {
+        Daemon {
+            registry,
+            pool: Pool::new(),
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+        }
+    }
+
+    pub fn pool(mut self, pool: Pool) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    pub async fn run<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path)?,
+            Ok(_) => return Err(io::Error::from(io::ErrorKind::AlreadyExists).into()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let listener = Async::<UnixListener>::bind(path)?;
+        let serve = async {
+            listener
+                .incoming()
+                .for_each_concurrent(None, |stream| async move {
+                    match stream {
+                        Ok(stream) => {
+                            let _ = self.handle(stream).await;
+                        }
+                        Err(_e) => {
+                            log!(warn, error = %_e, "failed to accept daemon connection");
+                        }
+                    }
+                })
+                .await;
+            Ok::<_, Error>(())
+        };
+        let refresh = self.refresh();
+        pin_mut!(serve, refresh);
+        match select(serve, refresh).await {
+            Either::Left((result, _)) | Either::Right((result, _)) => result,
+        }
+    }
+
+    async fn refresh(&self) -> Result<(), Error> {
+        loop {
+            if let Err(_e) = self.registry.refresh(DISCOVERY_WINDOW).await {
+                log!(warn, error = %_e, "background discovery failed");
+            }
+            self.pool.prune();
+            Timer::after(self.refresh_interval).await;
+        }
+    }
+
+    async fn handle(&self, stream: Async<UnixStream>) -> Result<(), Error> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).await? > 0 {
+            if !line.trim().is_empty() {
+                let response = match self.execute(line.trim()).await {
+                    Ok(result) => json!({ "ok": true, "result": result }),
+                    Err(e) => json!({ "ok": false, "error": e.to_string() }),
+                };
+                let stream = reader.get_mut();
+                stream
+                    .write_all(format!("{}\n", response).as_bytes())
+                    .await?;
+                stream.flush().await?;
+            }
+            line.clear();
+        }
+        Ok(())
+    }
+
+    async fn execute(&self, line: &str) -> Result<Value, Error> {
+        match Command::parse(line)? {
+            Command::List => Ok(self.registry.to_json()),
+            Command::Say { device } => {
+                let (message, options) = request::parse(line.as_bytes());
+                if message.is_empty() {
+                    return Err(Error::InvalidRequest("message is empty"));
+                }
+                let connection = self.connection(&device).await?;
+                let handle = connection.say_with_options(message, options).await?;
+                Ok(json!({ "media_session_id": handle.media_session_id() }))
+            }
+            Command::Volume { device, level } => {
+                let connection = self.connection(&device).await?;
+                if let Some(level) = level {
+                    connection.set_volume(level).await?;
+                }
+                Ok(json!({ "volume": connection.volume().await? }))
+            }
+            Command::Status { device } => {
+                let connection = self.connection(&device).await?;
+                Ok(request::summary(connection.status().await?))
+            }
+        }
+    }
+
+    async fn connection(&self, name: &str) -> Result<Arc<Connection>, Error> {
+        let target = self
+            .registry
+            .cached(name)
+            .ok_or_else(|| Error::DeviceNotFound(name.to_string()))?
+            .target();
+        let connection = self.pool.get(&target).await?;
+        if connection.health().connected {
+            return Ok(connection);
+        }
+        self.pool.evict(&target);
+        self.pool.get(&target).await
+    }
+}
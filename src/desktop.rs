@@ -0,0 +1,152 @@
+use std::{thread, time::Duration};
+
+use dbus::{blocking::Connection as Bus, channel::MatchingReceiver, message::MatchRule, Message};
+use futures::{channel::mpsc, StreamExt};
+
+use crate::{runtime::unblock, Connection, Error, Group};
+
+const INTERFACE: &str = "org.freedesktop.Notifications";
+const BUS_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+struct Notification {
+    app: String,
+    summary: String,
+    body: String,
+}
+
+pub struct DesktopNotifications {
+    group: Group,
+    include_body: bool,
+    ignored: Vec<String>,
+}
+
+impl DesktopNotifications {
+    pub fn new(group: Group) -> Self {
+        DesktopNotifications {
+            group,
+            include_body: true,
+            ignored: Vec::new(),
+        }
+    }
+
+    pub fn device(mut self, connection: Connection) -> Self {
+        self.group.push(connection);
+        self
+    }
+
+    pub fn summary_only(mut self) -> Self {
+        self.include_body = false;
+        self
+    }
+
+    pub fn ignore_app<T: Into<String>>(mut self, app: T) -> Self {
+        self.ignored.push(app.into());
+        self
+    }
+
+    pub async fn run(self) -> Result<(), Error> {
+        let bus = unblock(monitor).await?;
+        let (sender, mut notifications) = mpsc::unbounded();
+        let receiver = sender.clone();
+        bus.start_receive(
+            MatchRule::new_method_call(),
+            Box::new(move |message, _| {
+                if let Some(notification) = parse(&message) {
+                    let _ = receiver.unbounded_send(notification);
+                }
+                true
+            }),
+        );
+        thread::spawn(move || {
+            while !sender.is_closed() {
+                if let Err(_e) = bus.process(POLL_INTERVAL) {
+                    log!(warn, error = %_e, "d-bus connection lost");
+                    return;
+                }
+            }
+        });
+        while let Some(notification) = notifications.next().await {
+            if self
+                .ignored
+                .iter()
+                .any(|app| app.eq_ignore_ascii_case(&notification.app))
+            {
+                continue;
+            }
+            let message = self.message(&notification);
+            if message.is_empty() {
+                continue;
+            }
+            let outcome = self.group.say(message).await;
+            if !outcome.all_ok() {
+                log!(warn, app = %notification.app, outcome = %outcome, "desktop notification failed");
+            }
+        }
+        Err(Error::Protocol("d-bus connection lost"))
+    }
+
+    fn message(&self, notification: &Notification) -> String {
+        let summary = strip_markup(&notification.summary);
+        let body = strip_markup(&notification.body);
+        if !self.include_body || body.is_empty() {
+            return summary;
+        }
+        if summary.is_empty() {
+            return body;
+        }
+        if summary.ends_with(|c: char| c.is_ascii_punctuation()) {
+            format!("{} {}", summary, body)
+        } else {
+            format!("{}. {}", summary, body)
+        }
+    }
+}
+
+fn monitor() -> Result<Bus, Error> {
+    let bus = Bus::new_session()?;
+    let rule = MatchRule::new_method_call()
+        .with_interface(INTERFACE)
+        .with_member("Notify");
+    bus.with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", BUS_TIMEOUT)
+        .method_call::<(), _, _, _>(
+            "org.freedesktop.DBus.Monitoring",
+            "BecomeMonitor",
+            (vec![rule.match_str()], 0u32),
+        )?;
+    Ok(bus)
+}
+
+fn parse(message: &Message) -> Option<Notification> {
+    if message.interface().as_deref() != Some(INTERFACE)
+        || message.member().as_deref() != Some("Notify")
+    {
+        return None;
+    }
+    let (app, _, _, summary, body) = message
+        .read5::<String, u32, String, String, String>()
+        .ok()?;
+    Some(Notification { app, summary, body })
+}
+
+fn strip_markup(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    plain
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,348 @@
+use std::{
+    convert::TryFrom,
+    net::{Shutdown, SocketAddr, TcpStream},
+    sync::Arc,
+};
+
+use futures::lock::Mutex;
+use serde_json::{json, Value};
+
+use crate::{
+    AppStatus, AsyncCastDevice, AsyncTransport, ConnectOptions, Error, IdleReason, Image, Media,
+    MediaCommands, MediaStatus, Metadata, PlayerState, StreamType, DEFAULT_DESTINATION_ID,
+    NAMESPACE_CONNECTION, NAMESPACE_MEDIA, NAMESPACE_RECEIVER,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct ReceiverStatus {
+    pub(crate) applications: Vec<AppStatus>,
+    pub(crate) volume: Option<f32>,
+    pub(crate) muted: bool,
+    pub(crate) standby: bool,
+    pub(crate) active_input: bool,
+}
+
+pub(crate) enum Incoming {
+    Closed,
+    Receiver(ReceiverStatus),
+    Media(Vec<MediaStatus>),
+    Other,
+}
+
+#[derive(Clone)]
+pub(crate) struct Device {
+    pub(crate) inner: Arc<Mutex<AsyncCastDevice>>,
+    socket: Arc<TcpStream>,
+}
+
+impl Device {
+    pub(crate) async fn open(addr: SocketAddr, options: &ConnectOptions) -> Result<Self, Error> {
+        let transport = AsyncTransport::connect_with(addr, options.tls()?).await?;
+        let socket = Arc::new(transport.socket()?);
+        let mut device = AsyncCastDevice::from_transport(transport).await?;
+        if options.destination_id != DEFAULT_DESTINATION_ID {
+            device.connect_to(&options.destination_id).await?;
+        }
+        Ok(Device {
+            inner: Arc::new(Mutex::new(device)),
+            socket,
+        })
+    }
+
+    /// Closes the socket underneath any request still waiting on it, so a timed-out command
+    /// cannot keep the old connection alive.
+    pub(crate) fn shutdown(&self) {
+        let _ = self.socket.shutdown(Shutdown::Both);
+    }
+
+    pub(crate) async fn ping(&self) -> Result<(), Error> {
+        self.inner.lock().await.ping().await
+    }
+
+    pub(crate) async fn connect(&self, destination: &str) -> Result<(), Error> {
+        self.inner.lock().await.connect_to(destination).await
+    }
+
+    pub(crate) async fn disconnect(&self, destination: &str) -> Result<(), Error> {
+        self.send(
+            destination,
+            NAMESPACE_CONNECTION,
+            json!({ "type": "CLOSE" }),
+        )
+        .await
+    }
+
+    pub(crate) async fn send(
+        &self,
+        destination: &str,
+        namespace: &str,
+        payload: Value,
+    ) -> Result<(), Error> {
+        self.inner
+            .lock()
+            .await
+            .send(destination, namespace, payload)
+            .await
+    }
+
+    pub(crate) async fn request(
+        &self,
+        destination: &str,
+        namespace: &str,
+        payload: Value,
+    ) -> Result<Value, Error> {
+        let response = self
+            .inner
+            .lock()
+            .await
+            .request(destination, namespace, payload)
+            .await?;
+        match response["type"].as_str() {
+            Some(
+                kind @ ("INVALID_REQUEST"
+                | "INVALID_PLAYER_STATE"
+                | "LAUNCH_ERROR"
+                | "LOAD_CANCELLED"
+                | "LOAD_FAILED"),
+            ) => Err(Error::Rejected(kind.to_owned())),
+            _ => Ok(response),
+        }
+    }
+
+    pub(crate) async fn receive(&self) -> Result<Incoming, Error> {
+        let message = self.inner.lock().await.receive().await?;
+        let body = match message.to_json() {
+            Some(body) => body,
+            None => return Ok(Incoming::Other),
+        };
+        Ok(match (message.namespace.as_str(), body["type"].as_str()) {
+            (NAMESPACE_CONNECTION, Some("CLOSE")) => Incoming::Closed,
+            (NAMESPACE_RECEIVER, Some("RECEIVER_STATUS")) => {
+                Incoming::Receiver(receiver_status(&body["status"]))
+            }
+            (NAMESPACE_MEDIA, Some("MEDIA_STATUS")) => Incoming::Media(media_statuses(&body)),
+            _ => Incoming::Other,
+        })
+    }
+
+    pub(crate) async fn receiver_status(&self) -> Result<ReceiverStatus, Error> {
+        let response = self
+            .request(
+                DEFAULT_DESTINATION_ID,
+                NAMESPACE_RECEIVER,
+                json!({ "type": "GET_STATUS" }),
+            )
+            .await?;
+        Ok(receiver_status(&response["status"]))
+    }
+
+    pub(crate) async fn launch(&self, app_id: &str) -> Result<AppStatus, Error> {
+        let response = self
+            .request(
+                DEFAULT_DESTINATION_ID,
+                NAMESPACE_RECEIVER,
+                json!({ "type": "LAUNCH", "appId": app_id }),
+            )
+            .await?;
+        receiver_status(&response["status"])
+            .applications
+            .into_iter()
+            .find(|app| app.app_id == app_id)
+            .ok_or(Error::Protocol("app did not launch"))
+    }
+
+    pub(crate) async fn stop_app(&self, session_id: &str) -> Result<(), Error> {
+        self.request(
+            DEFAULT_DESTINATION_ID,
+            NAMESPACE_RECEIVER,
+            json!({ "type": "STOP", "sessionId": session_id }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn set_volume(&self, level: f32) -> Result<(), Error> {
+        self.request(
+            DEFAULT_DESTINATION_ID,
+            NAMESPACE_RECEIVER,
+            json!({ "type": "SET_VOLUME", "volume": { "level": level } }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn load(
+        &self,
+        transport_id: &str,
+        session_id: &str,
+        media: &Media,
+    ) -> Result<Vec<MediaStatus>, Error> {
+        let response = self
+            .request(
+                transport_id,
+                NAMESPACE_MEDIA,
+                json!({
+                    "type": "LOAD",
+                    "sessionId": session_id,
+                    "autoplay": true,
+                    "currentTime": 0,
+                    "media": media_json(media),
+                }),
+            )
+            .await?;
+        Ok(media_statuses(&response))
+    }
+
+    pub(crate) async fn media_status(
+        &self,
+        transport_id: &str,
+        media_session_id: Option<i32>,
+    ) -> Result<Vec<MediaStatus>, Error> {
+        let mut payload = json!({ "type": "GET_STATUS" });
+        if let Some(id) = media_session_id {
+            payload["mediaSessionId"] = id.into();
+        }
+        let response = self.request(transport_id, NAMESPACE_MEDIA, payload).await?;
+        Ok(media_statuses(&response))
+    }
+
+    pub(crate) async fn control(
+        &self,
+        transport_id: &str,
+        media_session_id: i32,
+        mut payload: Value,
+    ) -> Result<Option<MediaStatus>, Error> {
+        payload["mediaSessionId"] = media_session_id.into();
+        let response = self.request(transport_id, NAMESPACE_MEDIA, payload).await?;
+        Ok(media_statuses(&response)
+            .into_iter()
+            .find(|status| status.media_session_id == media_session_id))
+    }
+}
+
+fn receiver_status(status: &Value) -> ReceiverStatus {
+    ReceiverStatus {
+        applications: status["applications"]
+            .as_array()
+            .map(|apps| apps.iter().filter_map(app_status).collect())
+            .unwrap_or_default(),
+        volume: status["volume"]["level"].as_f64().map(|level| level as f32),
+        muted: status["volume"]["muted"].as_bool().unwrap_or(false),
+        standby: status["isStandBy"].as_bool().unwrap_or(false),
+        active_input: status["isActiveInput"].as_bool().unwrap_or(false),
+    }
+}
+
+fn app_status(app: &Value) -> Option<AppStatus> {
+    let text = |key: &str| app[key].as_str().unwrap_or_default().to_owned();
+    Some(AppStatus {
+        app_id: app["appId"].as_str()?.to_owned(),
+        display_name: text("displayName"),
+        status_text: text("statusText"),
+        session_id: text("sessionId"),
+        transport_id: text("transportId"),
+        supports_media: app["namespaces"].as_array().is_some_and(|namespaces| {
+            namespaces
+                .iter()
+                .any(|namespace| namespace["name"] == NAMESPACE_MEDIA)
+        }),
+    })
+}
+
+fn media_statuses(response: &Value) -> Vec<MediaStatus> {
+    response["status"]
+        .as_array()
+        .map(|entries| entries.iter().filter_map(media_status).collect())
+        .unwrap_or_default()
+}
+
+fn media_status(entry: &Value) -> Option<MediaStatus> {
+    Some(MediaStatus {
+        media_session_id: i32::try_from(entry["mediaSessionId"].as_i64()?).ok()?,
+        player_state: match entry["playerState"].as_str()? {
+            "PLAYING" => PlayerState::Playing,
+            "BUFFERING" => PlayerState::Buffering,
+            "PAUSED" => PlayerState::Paused,
+            _ => PlayerState::Idle,
+        },
+        idle_reason: match entry["idleReason"].as_str() {
+            Some("CANCELLED") => Some(IdleReason::Cancelled),
+            Some("INTERRUPTED") => Some(IdleReason::Interrupted),
+            Some("FINISHED") => Some(IdleReason::Finished),
+            Some("ERROR") => Some(IdleReason::Error),
+            _ => None,
+        },
+        current_time: entry["currentTime"].as_f64().map(|time| time as f32),
+        duration: entry["media"]["duration"]
+            .as_f64()
+            .map(|duration| duration as f32),
+        commands: MediaCommands::from_bits(
+            entry["supportedMediaCommands"]
+                .as_u64()
+                .and_then(|bits| u32::try_from(bits).ok())
+                .unwrap_or_default(),
+        ),
+    })
+}
+
+fn media_json(media: &Media) -> Value {
+    let mut json = json!({
+        "contentId": media.content_id,
+        "contentType": media.content_type,
+        "streamType": match media.stream_type {
+            StreamType::Buffered => "BUFFERED",
+            StreamType::Live => "LIVE",
+            _ => "NONE",
+        },
+    });
+    insert(&mut json, "duration", media.duration);
+    if let Some(metadata) = &media.metadata {
+        json["metadata"] = metadata_json(metadata);
+    }
+    json
+}
+
+fn metadata_json(metadata: &Metadata) -> Value {
+    match metadata {
+        Metadata::Generic(generic) => {
+            let mut json = json!({ "metadataType": 0, "images": images_json(&generic.images) });
+            insert(&mut json, "title", generic.title.clone());
+            insert(&mut json, "subtitle", generic.subtitle.clone());
+            insert(&mut json, "releaseDate", generic.release_date.clone());
+            json
+        }
+        Metadata::MusicTrack(track) => {
+            let mut json = json!({ "metadataType": 3, "images": images_json(&track.images) });
+            insert(&mut json, "albumName", track.album_name.clone());
+            insert(&mut json, "title", track.title.clone());
+            insert(&mut json, "albumArtist", track.album_artist.clone());
+            insert(&mut json, "artist", track.artist.clone());
+            insert(&mut json, "composer", track.composer.clone());
+            insert(&mut json, "trackNumber", track.track_number);
+            insert(&mut json, "discNumber", track.disc_number);
+            insert(&mut json, "releaseDate", track.release_date.clone());
+            json
+        }
+        _ => json!({ "metadataType": 0 }),
+    }
+}
+
+fn images_json(images: &[Image]) -> Value {
+    images
+        .iter()
+        .map(|image| {
+            let mut json = json!({ "url": image.url });
+            if let Some((width, height)) = image.dimensions {
+                json["width"] = width.into();
+                json["height"] = height.into();
+            }
+            json
+        })
+        .collect()
+}
+
+fn insert<T: Into<Value>>(json: &mut Value, key: &str, value: Option<T>) {
+    if let Some(value) = value {
+        json[key] = value.into();
+    }
+}
@@ -0,0 +1,108 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use futures::{Stream, TryStreamExt};
+use mdns::{Record, RecordKind};
+
+use crate::{parse, responses, DiscoverOptions, Error, Target};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawRecordKind {
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    Ptr(String),
+    Srv {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: String,
+    },
+    Txt(Vec<(String, String)>),
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawRecord {
+    pub name: String,
+    pub ttl: u32,
+    pub kind: RawRecordKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub target: Option<Target>,
+    pub answers: Vec<RawRecord>,
+    pub additional: Vec<RawRecord>,
+}
+
+impl RawResponse {
+    pub fn srv_target(&self) -> Option<&str> {
+        self.records().find_map(|record| match &record.kind {
+            RawRecordKind::Srv { target, .. } => Some(target.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn txt(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.records()
+            .filter_map(|record| match &record.kind {
+                RawRecordKind::Txt(pairs) => Some(pairs),
+                _ => None,
+            })
+            .flatten()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &RawRecord> {
+        self.answers.iter().chain(&self.additional)
+    }
+}
+
+impl From<&Record> for RawRecord {
+    fn from(record: &Record) -> Self {
+        let kind = match &record.kind {
+            RecordKind::A(ip) => RawRecordKind::A(*ip),
+            RecordKind::AAAA(ip) => RawRecordKind::Aaaa(*ip),
+            RecordKind::PTR(name) => RawRecordKind::Ptr(name.clone()),
+            RecordKind::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => RawRecordKind::Srv {
+                priority: *priority,
+                weight: *weight,
+                port: *port,
+                target: target.clone(),
+            },
+            RecordKind::TXT(items) => RawRecordKind::Txt(
+                items
+                    .iter()
+                    .map(|item| match item.split_once('=') {
+                        Some((key, value)) => (key.to_string(), value.to_string()),
+                        None => (item.clone(), String::new()),
+                    })
+                    .collect(),
+            ),
+            other => RawRecordKind::Other(format!("{:?}", other)),
+        };
+        RawRecord {
+            name: record.name.clone(),
+            ttl: record.ttl,
+            kind,
+        }
+    }
+}
+
+impl DiscoverOptions {
+    pub fn discover_verbose(self) -> impl Stream<Item = Result<RawResponse, Error>> {
+        responses(&self).map_ok(move |response| RawResponse {
+            target: parse(&response, &self),
+            answers: response.answers.iter().map(RawRecord::from).collect(),
+            additional: response.additional.iter().map(RawRecord::from).collect(),
+        })
+    }
+}
+
+pub fn discover_verbose() -> impl Stream<Item = Result<RawResponse, Error>> {
+    DiscoverOptions::default().discover_verbose()
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use crate::{chime::wav, Connection, Error, GenericMediaMetadata, Media, Metadata, StreamType};
+
+const SILENCE_SAMPLE_RATE: u32 = 8000;
+const DISPLAY_DURATION: Duration = Duration::from_secs(10);
+
+impl Connection {
+    pub async fn display<T: Into<String>>(&self, message: T) -> Result<(), Error> {
+        self.display_for(message, DISPLAY_DURATION).await
+    }
+
+    pub async fn display_for<T: Into<String>>(
+        &self,
+        message: T,
+        duration: Duration,
+    ) -> Result<(), Error> {
+        if !self.supports_video() {
+            return Err(Error::Unsupported("display"));
+        }
+        let server = self.audio_server()?;
+        let path = format!("/display/{}", duration.as_millis());
+        let clip = match server.get(&path) {
+            Some(clip) => clip,
+            None => {
+                let count = (duration.as_secs_f32() * SILENCE_SAMPLE_RATE as f32) as usize;
+                let silence = wav(SILENCE_SAMPLE_RATE, &vec![0; count]);
+                server.serve_at(&path, silence, "audio/wav")
+            }
+        };
+        self.play(Media {
+            stream_type: StreamType::Buffered,
+            duration: Some(duration.as_secs_f32()),
+            metadata: Some(Metadata::Generic(GenericMediaMetadata {
+                title: Some(message.into()),
+                subtitle: None,
+                images: Vec::new(),
+                release_date: None,
+            })),
+            content_type: clip.content_type,
+            content_id: clip.url,
+        })
+        .await
+    }
+}
@@ -0,0 +1,72 @@
+use std::{any::Any, collections::HashMap, net::IpAddr, sync::Arc, thread, time::Duration};
+
+use futures::{channel::mpsc, Stream};
+use zeroconf::{prelude::*, MdnsBrowser, ServiceDiscovery, ServiceType};
+
+use crate::Error;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(crate) struct Service {
+    pub(crate) ip: IpAddr,
+    pub(crate) port: u16,
+    pub(crate) host: Option<String>,
+    pub(crate) txt: HashMap<String, String>,
+}
+
+pub(crate) fn browse(service: &str) -> impl Stream<Item = Result<Service, Error>> {
+    let (sender, receiver) = mpsc::unbounded();
+    let service = service.to_owned();
+    thread::spawn(move || {
+        if let Err(e) = run(&service, &sender) {
+            let _ = sender.unbounded_send(Err(e.into()));
+        }
+    });
+    receiver
+}
+
+fn run(
+    service: &str,
+    sender: &mpsc::UnboundedSender<Result<Service, Error>>,
+) -> zeroconf::Result<()> {
+    let mut parts = service.trim_end_matches(".local").split('.');
+    let (name, protocol) = match (parts.next(), parts.next()) {
+        (Some(name), Some(protocol)) => (
+            name.trim_start_matches('_'),
+            protocol.trim_start_matches('_'),
+        ),
+        _ => return Ok(()),
+    };
+    let mut browser = MdnsBrowser::new(ServiceType::new(name, protocol)?);
+    let discovered = sender.clone();
+    browser.set_service_discovered_callback(Box::new(
+        move |result: zeroconf::Result<ServiceDiscovery>, _: Option<Arc<dyn Any>>| {
+            let service = result.map_err(Error::from).map(|service| {
+                service.address().parse().ok().map(|ip| Service {
+                    ip,
+                    port: *service.port(),
+                    host: Some(service.host_name().clone()),
+                    txt: service
+                        .txt()
+                        .as_ref()
+                        .map(|txt| txt.iter().collect())
+                        .unwrap_or_default(),
+                })
+            });
+            match service {
+                Ok(Some(service)) => {
+                    let _ = discovered.unbounded_send(Ok(service));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let _ = discovered.unbounded_send(Err(e));
+                }
+            }
+        },
+    ));
+    let event_loop = browser.browse_services()?;
+    while !sender.is_closed() {
+        event_loop.poll(POLL_INTERVAL)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::collections::{HashSet, VecDeque};
+
+use futures::{future::Either, stream, Stream, StreamExt};
+
+use crate::{
+    device::{Device, Incoming},
+    AppStatus, Connection, Error, MediaStatus,
+};
+
+#[derive(Debug, Clone)]
+pub enum CastEvent {
+    AppChanged(Option<AppStatus>),
+    VolumeChanged { level: Option<f32>, muted: bool },
+    StandbyChanged(bool),
+    Media(MediaStatus),
+    Disconnected,
+}
+
+struct Events {
+    device: Device,
+    session: Option<String>,
+    volume: Option<(Option<f32>, bool)>,
+    standby: Option<bool>,
+    transports: HashSet<String>,
+    pending: VecDeque<CastEvent>,
+    closed: bool,
+}
+
+impl Events {
+    fn new(device: Device) -> Self {
+        Events {
+            device,
+            session: None,
+            volume: None,
+            standby: None,
+            transports: HashSet::new(),
+            pending: VecDeque::new(),
+            closed: false,
+        }
+    }
+
+    async fn next(mut self) -> Option<(CastEvent, Self)> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some((event, self));
+            }
+            if self.closed {
+                return None;
+            }
+            match self.device.receive().await {
+                Ok(message) => self.handle(message).await,
+                Err(_) => {
+                    self.closed = true;
+                    self.pending.push_back(CastEvent::Disconnected);
+                }
+            }
+        }
+    }
+
+    async fn handle(&mut self, message: Incoming) {
+        match message {
+            Incoming::Closed => self.transports.clear(),
+            Incoming::Receiver(status) => {
+                let app = status.applications.into_iter().next();
+                let session = app.as_ref().map(|app| app.session_id.clone());
+                if session != self.session {
+                    self.session = session;
+                    if let Some(app) = app.as_ref().filter(|app| app.supports_media) {
+                        if self.transports.insert(app.transport_id.clone()) {
+                            let _ = self.device.connect(&app.transport_id).await;
+                        }
+                    }
+                    self.pending.push_back(CastEvent::AppChanged(app));
+                }
+                let volume = (status.volume, status.muted);
+                if self.volume != Some(volume) {
+                    self.volume = Some(volume);
+                    self.pending.push_back(CastEvent::VolumeChanged {
+                        level: volume.0,
+                        muted: volume.1,
+                    });
+                }
+                if self.standby != Some(status.standby) {
+                    self.standby = Some(status.standby);
+                    self.pending
+                        .push_back(CastEvent::StandbyChanged(status.standby));
+                }
+            }
+            Incoming::Media(status) => {
+                self.pending
+                    .extend(status.into_iter().map(CastEvent::Media));
+            }
+            Incoming::Other => {}
+        }
+    }
+}
+
+impl Connection {
+    pub fn events(&self) -> impl Stream<Item = CastEvent> {
+        let addr = self.link.id.addr;
+        let options = self.link.options.clone();
+        #[cfg(feature = "message-tap")]
+        let tap = self.link.tap.clone();
+        stream::once(async move {
+            let device = Device::open(addr, &options).await?;
+            #[cfg(feature = "message-tap")]
+            device.forward_to(&tap).await;
+            Ok::<_, Error>(device)
+        })
+        .flat_map(|device| match device {
+            Ok(device) => Either::Left(stream::unfold(Events::new(device), Events::next)),
+            Err(_) => Either::Right(stream::iter(Some(CastEvent::Disconnected))),
+        })
+    }
+}
@@ -0,0 +1,81 @@
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryFilter {
+    name: Option<String>,
+    model: Option<String>,
+    uuid: Option<String>,
+}
+
+impl DiscoveryFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.name = Some(pattern.into());
+        self
+    }
+
+    pub fn model<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.model = Some(pattern.into());
+        self
+    }
+
+    pub fn uuid<T: AsRef<str>>(mut self, uuid: T) -> Self {
+        self.uuid = Some(normalize_uuid(uuid.as_ref()));
+        self
+    }
+
+    pub(crate) fn matches(&self, name: &str, model: Option<&str>, uuid: Option<&str>) -> bool {
+        if let Some(pattern) = &self.name {
+            if !glob(pattern, name) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.model {
+            if !model.is_some_and(|model| glob(pattern, model)) {
+                return false;
+            }
+        }
+        if let Some(expected) = &self.uuid {
+            if uuid.map(normalize_uuid).as_ref() != Some(expected) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+pub(crate) fn normalize_uuid(uuid: &str) -> String {
+    uuid.chars()
+        .filter(|c| *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
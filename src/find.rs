@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use async_io::Timer;
+use futures::{pin_mut, StreamExt};
+
+use crate::{discover, filter::normalize_uuid, ConnectOptions, Connection, Error, Target};
+
+const FIND_ROUND: Duration = Duration::from_secs(5);
+const FIND_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+impl Connection {
+    pub async fn find(query: &str, timeout: Duration) -> Result<Connection, Error> {
+        Self::find_with(query, timeout, ConnectOptions::default()).await
+    }
+
+    pub async fn find_with(
+        query: &str,
+        timeout: Duration,
+        options: ConnectOptions,
+    ) -> Result<Connection, Error> {
+        let deadline = Instant::now() + timeout;
+        let uuid = normalize_uuid(query);
+        let matches = |target: &Target| {
+            target.name().eq_ignore_ascii_case(query)
+                || target.uuid().map(normalize_uuid).as_ref() == Some(&uuid)
+        };
+        let mut last_error = None;
+        loop {
+            let round = deadline
+                .saturating_duration_since(Instant::now())
+                .min(FIND_ROUND);
+            let targets = discover().take_until(Timer::after(round));
+            pin_mut!(targets);
+            while let Some(target) = targets.next().await {
+                let target = target?;
+                if !matches(&target) {
+                    continue;
+                }
+                match target.connect_with(options.clone()).await {
+                    Ok(connection) => return Ok(connection),
+                    Err(e) => {
+                        last_error = Some(e);
+                        break;
+                    }
+                }
+            }
+            if Instant::now() + FIND_RETRY_DELAY >= deadline {
+                return Err(last_error.unwrap_or_else(|| Error::DeviceNotFound(query.to_string())));
+            }
+            Timer::after(FIND_RETRY_DELAY).await;
+        }
+    }
+}
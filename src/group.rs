@@ -0,0 +1,192 @@
+use std::{borrow::Cow, future::Future, sync::Arc, time::Duration};
+
+use async_io::Timer;
+use futures::{
+    future::{join_all, ready},
+    pin_mut, stream, Stream, StreamExt, TryFutureExt, TryStreamExt,
+};
+use serde_json::Value;
+
+use crate::{
+    discover_for, filter::normalize_uuid, http::TcpClient, info::eureka_info, BroadcastOutcome,
+    ConnectOptions, Connection, Error, Target,
+};
+
+const GROUP_MODEL: &str = "Google Cast Group";
+const MULTIZONE_PATH: &str = "/setup/eureka_info?params=multizone";
+const DEFAULT_CONCURRENCY: usize = 8;
+
+#[derive(Default)]
+pub struct Group {
+    connections: Vec<Connection>,
+}
+
+impl Group {
+    pub fn new(connections: Vec<Connection>) -> Self {
+        Group { connections }
+    }
+
+    pub async fn connect<I: IntoIterator<Item = Target>>(
+        targets: I,
+        concurrency: usize,
+    ) -> (Self, BroadcastOutcome) {
+        let mut group = Group::default();
+        let mut outcome = BroadcastOutcome::new();
+        let connections = connect_all(targets, concurrency);
+        pin_mut!(connections);
+        while let Some(result) = connections.next().await {
+            match result {
+                Ok(connection) => {
+                    outcome.record(&connection, Ok(()));
+                    group.push(connection);
+                }
+                Err(e) => outcome.fail(e),
+            }
+        }
+        (group, outcome)
+    }
+
+    pub async fn expand(
+        target: &Target,
+        timeout: Duration,
+    ) -> Result<(Self, BroadcastOutcome), Error> {
+        let members = target.members(timeout).await?;
+        Ok(Group::connect(members, DEFAULT_CONCURRENCY).await)
+    }
+
+    pub fn push(&mut self, connection: Connection) {
+        self.connections.push(connection);
+    }
+
+    pub fn connections(&self) -> &[Connection] {
+        &self.connections
+    }
+
+    pub async fn say<'a, T: Into<Cow<'a, str>>>(&self, message: T) -> BroadcastOutcome {
+        let message = message.into();
+        let results = join_all(
+            self.connections
+                .iter()
+                .map(|connection| connection.say(message.as_ref()).map_ok(drop)),
+        )
+        .await;
+        let mut outcome = BroadcastOutcome::new();
+        for (connection, result) in self.connections.iter().zip(results) {
+            outcome.record(connection, result);
+        }
+        outcome
+    }
+
+    pub async fn say_sequential<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        delay_between: Duration,
+    ) -> BroadcastOutcome {
+        let message = message.into();
+        let mut outcome = BroadcastOutcome::new();
+        for (i, connection) in self.connections.iter().enumerate() {
+            if i > 0 && !delay_between.is_zero() {
+                Timer::after(delay_between).await;
+            }
+            outcome.record(connection, connection.say_and_wait(message.as_ref()).await);
+        }
+        outcome
+    }
+
+    /// Runs `f` with every member that accepted `level` set to it, then puts those members
+    /// back at their previous volume. The outcome records, per member, whether its volume was
+    /// both changed and restored; `f` still runs when some members fail.
+    pub async fn with_volume<'a, F, Fut, T>(
+        &'a self,
+        level: f32,
+        f: F,
+    ) -> (Result<T, Error>, BroadcastOutcome)
+    where
+        F: FnOnce(&'a Group) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let changed = join_all(self.connections.iter().map(|connection| async move {
+            let previous = connection.volume().await?;
+            connection.set_volume(level).await?;
+            Ok::<_, Error>(previous)
+        }))
+        .await;
+        let result = f(self).await;
+        let restored = join_all(self.connections.iter().zip(changed).map(
+            |(connection, changed)| async move {
+                let previous = changed?;
+                let restored = connection.set_volume(previous).await;
+                if let Err(_e) = &restored {
+                    log!(
+                        warn,
+                        device = %connection.device_id(),
+                        error = %_e,
+                        "failed to restore member volume"
+                    );
+                }
+                restored
+            },
+        ))
+        .await;
+        let mut outcome = BroadcastOutcome::new();
+        for (connection, result) in self.connections.iter().zip(restored) {
+            outcome.record(connection, result);
+        }
+        (result, outcome)
+    }
+}
+
+pub fn connect_all<I: IntoIterator<Item = Target>>(
+    targets: I,
+    concurrency: usize,
+) -> impl Stream<Item = Result<Connection, Error>> {
+    connect_all_with(targets, concurrency, ConnectOptions::default())
+}
+
+pub fn connect_all_with<I: IntoIterator<Item = Target>>(
+    targets: I,
+    concurrency: usize,
+    options: ConnectOptions,
+) -> impl Stream<Item = Result<Connection, Error>> {
+    stream::iter(targets)
+        .map(move |target| target.connect_with(options.clone()))
+        .buffer_unordered(concurrency.max(1))
+}
+
+impl Target {
+    pub fn is_group(&self) -> bool {
+        self.model.as_deref() == Some(GROUP_MODEL)
+    }
+
+    pub async fn members(&self, timeout: Duration) -> Result<Vec<Target>, Error> {
+        let uuid = match (&self.uuid, self.is_group()) {
+            (Some(uuid), true) => normalize_uuid(uuid),
+            _ => return Ok(vec![self.clone()]),
+        };
+        let devices: Vec<Target> = discover_for(timeout)
+            .try_filter(|target| ready(!target.is_group()))
+            .try_collect()
+            .await?;
+        let groups = join_all(
+            devices
+                .iter()
+                .map(|device| eureka_info(device, Arc::new(TcpClient), MULTIZONE_PATH)),
+        )
+        .await;
+        Ok(devices
+            .into_iter()
+            .zip(groups)
+            .filter(|(_, info)| info.as_ref().is_ok_and(|info| in_group(info, &uuid)))
+            .map(|(device, _)| device)
+            .collect())
+    }
+}
+
+fn in_group(info: &Value, uuid: &str) -> bool {
+    info["multizone"]["groups"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group["uuid"].as_str())
+        .any(|group| normalize_uuid(group) == uuid)
+}
@@ -0,0 +1,103 @@
+use std::net::SocketAddr;
+
+use chrono::{DateTime, Utc};
+
+use crate::{Connection, Error, Notifier};
+
+#[derive(Debug)]
+pub(crate) struct Tracker {
+    connected: bool,
+    last_heartbeat: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Tracker {
+            connected: true,
+            last_heartbeat: None,
+            last_error: None,
+        }
+    }
+}
+
+impl Tracker {
+    pub(crate) fn record<T>(&mut self, result: &Result<T, Error>) {
+        match result {
+            Ok(_) => self.connected = true,
+            Err(e) => {
+                if e.is_connection_error() || matches!(e, Error::Disconnected { .. }) {
+                    self.connected = false;
+                }
+                self.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// A heartbeat only succeeds once the device has answered with a PONG, so a failed one
+    /// means the device is not responding, whatever the error was.
+    pub(crate) fn heartbeat(&mut self, result: &Result<(), Error>) {
+        self.record(result);
+        match result {
+            Ok(()) => self.last_heartbeat = Some(Utc::now()),
+            Err(_) => self.connected = false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceHealth {
+    pub device: String,
+    pub addr: SocketAddr,
+    pub connected: bool,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    pub queue_depth: usize,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Health {
+    pub timestamp: DateTime<Utc>,
+    pub devices: Vec<DeviceHealth>,
+}
+
+impl Health {
+    pub fn is_healthy(&self) -> bool {
+        self.devices.iter().all(|device| device.connected)
+    }
+}
+
+impl Connection {
+    pub fn health(&self) -> DeviceHealth {
+        let tracker = self.link.health.lock().unwrap();
+        DeviceHealth {
+            device: self.link.id.name.clone(),
+            addr: self.link.id.addr,
+            connected: tracker.connected && !self.closed,
+            last_heartbeat: tracker.last_heartbeat,
+            queue_depth: self.queue.depth(),
+            last_error: tracker.last_error.clone(),
+        }
+    }
+}
+
+impl Notifier {
+    pub fn health(&self) -> Health {
+        let primary = self.connection().health();
+        let addr = primary.addr;
+        let mut devices = vec![primary];
+        devices.extend(
+            self.pool()
+                .connections()
+                .iter()
+                .map(|connection| connection.health())
+                .filter(|health| health.addr != addr),
+        );
+        Health {
+            timestamp: Utc::now(),
+            devices,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::{sync::Arc, time::Duration};
+
+use async_io::Timer;
+use futures::{future::ready, stream, Future, Stream, StreamExt};
+
+use crate::{runtime::spawn, Connection, Error, Link};
+
+impl Link {
+    pub(crate) async fn heartbeat(&self) -> Result<(), Error> {
+        let result = self
+            .command(|device| async move { device.ping().await })
+            .await;
+        if let Err(_e) = &result {
+            log!(warn, device = %self.id, error = %_e, "heartbeat failed");
+        }
+        self.health.lock().unwrap().heartbeat(&result);
+        result
+    }
+}
+
+impl Connection {
+    /// Each beat is a PING that only succeeds once the device answers with a PONG, so the
+    /// health it records reflects a device that is actually responding.
+    pub fn heartbeat(&self, interval: Duration) -> impl Stream<Item = Result<(), Error>> {
+        let link = Arc::downgrade(&self.link);
+        stream::unfold(link, move |link| async move {
+            Timer::after(interval).await;
+            let result = link.upgrade()?.heartbeat().await;
+            Some((result, link))
+        })
+    }
+
+    pub fn keep_alive(&self, interval: Duration) -> impl Future<Output = Result<(), Error>> {
+        let mut heartbeat = Box::pin(self.heartbeat(interval));
+        async move {
+            while let Some(result) = heartbeat.next().await {
+                result?;
+            }
+            Ok(())
+        }
+    }
+
+    pub fn spawn_heartbeat<F: FnMut(Error) + Send + 'static>(
+        &self,
+        interval: Duration,
+        mut on_failure: F,
+    ) {
+        let heartbeat = self.heartbeat(interval);
+        spawn(heartbeat.for_each(move |result| {
+            if let Err(e) = result {
+                on_failure(e);
+            }
+            ready(())
+        }));
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use crate::{info::string, runtime::unblock, DeviceId, Error, Outcome};
+
+const DEFAULT_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(rename_all = "lowercase", tag = "status"))]
+pub enum HistoryOutcome {
+    Completed,
+    Preempted,
+    Cancelled,
+    Suppressed,
+    Failed { error: String },
+}
+
+impl HistoryOutcome {
+    pub fn of(result: &Result<Outcome, Error>) -> Self {
+        match result {
+            Ok(Outcome::Completed) => HistoryOutcome::Completed,
+            Ok(Outcome::Preempted) => HistoryOutcome::Preempted,
+            Ok(Outcome::Cancelled) | Err(Error::Cancelled) => HistoryOutcome::Cancelled,
+            Ok(Outcome::Failed) => HistoryOutcome::Failed {
+                error: "playback failed".to_owned(),
+            },
+            Err(Error::Suppressed(_)) => HistoryOutcome::Suppressed,
+            Err(e) => HistoryOutcome::Failed {
+                error: e.to_string(),
+            },
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            HistoryOutcome::Completed => json!({ "status": "completed" }),
+            HistoryOutcome::Preempted => json!({ "status": "preempted" }),
+            HistoryOutcome::Cancelled => json!({ "status": "cancelled" }),
+            HistoryOutcome::Suppressed => json!({ "status": "suppressed" }),
+            HistoryOutcome::Failed { error } => json!({ "status": "failed", "error": error }),
+        }
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(match value["status"].as_str()? {
+            "completed" => HistoryOutcome::Completed,
+            "preempted" => HistoryOutcome::Preempted,
+            "cancelled" => HistoryOutcome::Cancelled,
+            "suppressed" => HistoryOutcome::Suppressed,
+            "failed" => HistoryOutcome::Failed {
+                error: string(value, "error")?,
+            },
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    pub device: String,
+    pub message: String,
+    pub outcome: HistoryOutcome,
+}
+
+impl HistoryEntry {
+    pub fn new<T: Into<String>>(device: &DeviceId, message: T, outcome: HistoryOutcome) -> Self {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            device: device.name.clone(),
+            message: message.into(),
+            outcome,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "timestamp": self.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            "device": self.device,
+            "message": self.message,
+            "outcome": self.outcome.to_json(),
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(HistoryEntry {
+            timestamp: DateTime::parse_from_rfc3339(value["timestamp"].as_str()?)
+                .ok()?
+                .with_timezone(&Utc),
+            device: string(value, "device")?,
+            message: string(value, "message")?,
+            outcome: HistoryOutcome::from_json(&value["outcome"])?,
+        })
+    }
+}
+
+pub struct History {
+    path: Option<PathBuf>,
+    capacity: usize,
+    entries: Mutex<VecDeque<HistoryEntry>>,
+    persisted: AtomicUsize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            path: None,
+            capacity: DEFAULT_CAPACITY,
+            entries: Mutex::new(VecDeque::new()),
+            persisted: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn open<P: Into<PathBuf>>(path: P, capacity: usize) -> Result<Self, Error> {
+        let path = path.into();
+        let (mut entries, mut lines) = (VecDeque::new(), 0);
+        match fs::read_to_string(&path) {
+            Ok(data) => {
+                for line in data.lines().filter(|line| !line.trim().is_empty()) {
+                    let entry = serde_json::from_str(line)?;
+                    let entry =
+                        HistoryEntry::from_json(&entry).ok_or(Error::InvalidRecord("history"))?;
+                    entries.push_back(entry);
+                    lines += 1;
+                    if entries.len() > capacity {
+                        entries.pop_front();
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        if lines > entries.len() {
+            fs::write(&path, serialize(&entries)?)?;
+        }
+        Ok(History {
+            path: Some(path),
+            capacity,
+            persisted: AtomicUsize::new(entries.len()),
+            entries: Mutex::new(entries),
+        })
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        let entries = self.entries.get_mut().unwrap();
+        while entries.len() > capacity {
+            entries.pop_front();
+        }
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn since(&self, time: DateTime<Utc>) -> Vec<HistoryEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.timestamp >= time)
+            .cloned()
+            .collect()
+    }
+
+    pub fn for_device(&self, name: &str) -> Vec<HistoryEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.device.eq_ignore_ascii_case(name))
+            .cloned()
+            .collect()
+    }
+
+    /// Appends to the log file until it holds twice the capacity, then rewrites it with only
+    /// the retained entries.
+    pub async fn record(&self, entry: HistoryEntry) -> Result<(), Error> {
+        let line = serialize(std::iter::once(&entry))?;
+        let compacted = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.push_back(entry);
+            while entries.len() > self.capacity {
+                entries.pop_front();
+            }
+            let persisted = self.persisted.fetch_add(1, Ordering::SeqCst) + 1;
+            if self.path.is_some() && persisted > self.capacity.max(1) * 2 {
+                self.persisted.store(entries.len(), Ordering::SeqCst);
+                Some(serialize(&*entries)?)
+            } else {
+                None
+            }
+        };
+        if let Some(path) = self.path.clone() {
+            unblock(move || match compacted {
+                Some(data) => fs::write(path, data),
+                None => OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?
+                    .write_all(&line),
+            })
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+fn serialize<'a, I: IntoIterator<Item = &'a HistoryEntry>>(entries: I) -> Result<Vec<u8>, Error> {
+    let mut data = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut data, &entry.to_json())?;
+        data.push(b'\n');
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_entries_in_the_serde_layout() {
+        let line = r#"{"timestamp":"2021-03-04T05:06:07.089Z","device":"Kitchen","message":"Door open","outcome":{"status":"failed","error":"timed out"}}"#;
+        let entry = HistoryEntry::from_json(&serde_json::from_str(line).unwrap()).unwrap();
+        assert_eq!(entry.device, "Kitchen");
+        assert_eq!(
+            entry.outcome,
+            HistoryOutcome::Failed {
+                error: "timed out".to_owned()
+            }
+        );
+        assert_eq!(
+            entry.to_json(),
+            serde_json::from_str::<Value>(line).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_outcomes() {
+        let value = json!({
+            "timestamp": "2021-03-04T05:06:07Z",
+            "device": "Kitchen",
+            "message": "Door open",
+            "outcome": { "status": "exploded" },
+        });
+        assert!(HistoryEntry::from_json(&value).is_none());
+        let value = json!({ "status": "failed" });
+        assert!(HistoryOutcome::from_json(&value).is_none());
+    }
+}
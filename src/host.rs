@@ -0,0 +1,58 @@
+use std::{
+    net::{SocketAddr, ToSocketAddrs},
+    time::Duration,
+};
+
+use async_io::Timer;
+use futures::{pin_mut, StreamExt};
+
+use crate::{discover, runtime::unblock, ConnectOptions, Connection, Error, Target};
+
+const HOST_LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl Target {
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    pub fn with_host<T: Into<String>>(mut self, host: T) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    pub async fn resolve_host(&self) -> Result<SocketAddr, Error> {
+        let host = match &self.host {
+            Some(host) => host.trim_end_matches('.').to_owned(),
+            None => return Ok(self.addr),
+        };
+        let port = self.addr.port();
+        let lookup = host.clone();
+        if let Ok(Some(addr)) =
+            unblock(move || Ok::<_, Error>((lookup.as_str(), port).to_socket_addrs()?.next())).await
+        {
+            return Ok(addr);
+        }
+        let targets = discover().take_until(Timer::after(HOST_LOOKUP_TIMEOUT));
+        pin_mut!(targets);
+        while let Some(target) = targets.next().await {
+            let target = target?;
+            let matches = target
+                .host()
+                .is_some_and(|other| other.trim_end_matches('.').eq_ignore_ascii_case(&host));
+            if matches {
+                return Ok(target.addr);
+            }
+        }
+        Err(Error::DeviceNotFound(host))
+    }
+
+    pub async fn connect_by_host(mut self, options: ConnectOptions) -> Result<Connection, Error> {
+        match self.resolve_host().await {
+            Ok(addr) => self.addr = addr,
+            Err(_e) => {
+                log!(warn, host = ?self.host, error = %_e, "failed to resolve host");
+            }
+        }
+        self.connect_with(options).await
+    }
+}
@@ -0,0 +1,54 @@
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+pub trait HttpClient: Send + Sync {
+    fn get(&self, addr: SocketAddr, path: &str) -> io::Result<HttpResponse>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpClient;
+
+impl HttpClient for TcpClient {
+    fn get(&self, addr: SocketAddr, path: &str) -> io::Result<HttpResponse> {
+        let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        write!(
+            stream,
+            "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            path, addr
+        )?;
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data)?;
+        parse_response(data)
+    }
+}
+
+fn parse_response(data: Vec<u8>) -> io::Result<HttpResponse> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed http response");
+    let split = data
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(invalid)?;
+    let head = std::str::from_utf8(&data[..split]).map_err(|_| invalid())?;
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(invalid)?;
+    Ok(HttpResponse {
+        status,
+        body: data[split + 4..].to_vec(),
+    })
+}
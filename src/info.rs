@@ -0,0 +1,122 @@
+use std::{collections::HashMap, convert::TryFrom, io::ErrorKind, net::SocketAddr, sync::Arc};
+
+use serde_json::Value;
+
+use crate::{
+    http::{HttpClient, TcpClient},
+    runtime::unblock,
+    Error, Target,
+};
+
+const EUREKA_PORT: u16 = 8008;
+const EUREKA_PATH: &str = "/setup/eureka_info?params=version,name,build_info,device_info";
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceInfo {
+    pub name: Option<String>,
+    pub version: Option<u32>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub build_info: BuildInfo,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub device_info: HardwareInfo,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuildInfo {
+    pub cast_build_revision: Option<String>,
+    pub system_build_number: Option<String>,
+    pub build_type: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct HardwareInfo {
+    pub manufacturer: Option<String>,
+    pub model_name: Option<String>,
+    pub product_name: Option<String>,
+    pub ssdp_udn: Option<String>,
+    pub mac_address: Option<String>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub capabilities: HashMap<String, Value>,
+}
+
+impl DeviceInfo {
+    fn from_json(value: &Value) -> Self {
+        DeviceInfo {
+            name: string(value, "name"),
+            version: value
+                .get("version")
+                .and_then(Value::as_u64)
+                .and_then(|version| u32::try_from(version).ok()),
+            build_info: BuildInfo::from_json(&value["build_info"]),
+            device_info: HardwareInfo::from_json(&value["device_info"]),
+        }
+    }
+}
+
+impl BuildInfo {
+    fn from_json(value: &Value) -> Self {
+        BuildInfo {
+            cast_build_revision: string(value, "cast_build_revision"),
+            system_build_number: string(value, "system_build_number"),
+            build_type: value
+                .get("build_type")
+                .and_then(Value::as_u64)
+                .and_then(|build_type| u32::try_from(build_type).ok()),
+        }
+    }
+}
+
+impl HardwareInfo {
+    fn from_json(value: &Value) -> Self {
+        HardwareInfo {
+            manufacturer: string(value, "manufacturer"),
+            model_name: string(value, "model_name"),
+            product_name: string(value, "product_name"),
+            ssdp_udn: string(value, "ssdp_udn"),
+            mac_address: string(value, "mac_address"),
+            capabilities: value
+                .get("capabilities")
+                .and_then(Value::as_object)
+                .map(|capabilities| capabilities.clone().into_iter().collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+pub(crate) fn string(value: &Value, key: &str) -> Option<String> {
+    value.get(key)?.as_str().map(str::to_owned)
+}
+
+pub async fn device_info(target: &Target) -> Result<DeviceInfo, Error> {
+    device_info_with(target, Arc::new(TcpClient)).await
+}
+
+pub async fn device_info_with(
+    target: &Target,
+    client: Arc<dyn HttpClient>,
+) -> Result<DeviceInfo, Error> {
+    let info = eureka_info(target, client, EUREKA_PATH).await?;
+    Ok(DeviceInfo::from_json(&info))
+}
+
+pub(crate) async fn eureka_info(
+    target: &Target,
+    client: Arc<dyn HttpClient>,
+    path: &'static str,
+) -> Result<Value, Error> {
+    let addr = SocketAddr::new(target.addr.ip(), EUREKA_PORT);
+    unblock(move || {
+        let response = client.get(addr, path).map_err(|e| match e.kind() {
+            ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset => Error::InfoUnavailable,
+            _ => Error::Io(e),
+        })?;
+        if response.status != 200 {
+            return Err(Error::InfoUnavailable);
+        }
+        Ok(serde_json::from_slice(&response.body)?)
+    })
+    .await
+}
@@ -1,202 +1,1423 @@
 use std::{
     borrow::Cow,
-    collections::HashSet,
-    net::SocketAddr,
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use blocking::unblock;
+use announcement::Ticket;
+use async_io::Timer;
+use batch::{Batch, Slot};
+use device::Device;
 use futures::{
-    future::{ready, Either},
-    ready,
-    stream::once,
-    FutureExt, Stream, StreamExt, TryStreamExt,
+    future::{ready, select, Either},
+    pin_mut, ready,
+    stream::{once, select_all},
+    Future, FutureExt, Stream, StreamExt, TryStreamExt,
 };
-use google_translate_tts::url;
+use health::Tracker;
 use mdns::RecordKind;
 use pin_project::pin_project;
-use rust_cast::{
-    channels::{
-        media::{Media, StreamType},
-        receiver::CastDeviceApp,
-    },
-    CastDevice,
-};
+use policy::{Admission, Recent};
+use priority::{Turn, Turns};
+use runtime::unblock;
 use thiserror::Error;
 
-const DEFAULT_DESTINATION_ID: &str = "receiver-0";
+macro_rules! log {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+    };
+}
+
+mod announcement;
+#[cfg(feature = "http-api")]
+mod api;
+mod app;
+mod batch;
+pub mod blocking;
+mod broadcast;
+mod cache;
+mod capabilities;
+mod chime;
+#[cfg(feature = "serialize")]
+mod config;
+#[cfg(all(unix, feature = "daemon"))]
+mod daemon;
+#[cfg(all(target_os = "linux", feature = "desktop-notifications"))]
+mod desktop;
+#[cfg(feature = "detect-language")]
+mod detect;
+mod device;
+mod diagnostics;
+mod display;
+#[cfg(feature = "dns-sd")]
+mod dnssd;
+mod events;
+mod filter;
+mod find;
+mod group;
+mod health;
+mod heartbeat;
+mod history;
+mod host;
+mod http;
+mod info;
+mod lifecycle;
+mod media;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod mix;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod multicast;
+mod notifier;
+mod options;
+mod pick;
+mod policy;
+mod pool;
+mod priority;
+mod probe;
+mod reconnect;
+mod registry;
+#[cfg(any(feature = "http-api", feature = "mqtt", all(unix, feature = "daemon")))]
+mod request;
+mod resume;
+mod retry;
+mod runtime;
+mod scheduler;
+mod server;
+mod session;
+mod settings;
+mod shutdown;
+mod standby;
+mod status;
+mod subtitles;
+#[cfg(feature = "message-tap")]
+mod tap;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod text;
+mod theme;
+mod tone;
+mod transcode;
+mod transport;
+mod tts;
+mod volume;
+mod watch;
+mod zone;
+
+pub use announcement::{Announcement, AnnouncementHandle, Outcome};
+#[cfg(feature = "http-api")]
+pub use api::Api;
+pub use broadcast::BroadcastOutcome;
+pub use cache::TtsCache;
+pub use capabilities::Capabilities;
+pub use chime::ChimeSource;
+#[cfg(feature = "serialize")]
+pub use config::{Config, DeviceAlias};
+#[cfg(all(unix, feature = "daemon"))]
+pub use daemon::Daemon;
+#[cfg(all(target_os = "linux", feature = "desktop-notifications"))]
+pub use desktop::DesktopNotifications;
+pub use diagnostics::{discover_verbose, RawRecord, RawRecordKind, RawResponse};
+pub use events::CastEvent;
+pub use filter::DiscoveryFilter;
+pub use group::{connect_all, connect_all_with, Group};
+pub use health::{DeviceHealth, Health};
+pub use history::{History, HistoryEntry, HistoryOutcome};
+pub use http::{HttpClient, HttpResponse, TcpClient};
+pub use info::{device_info, device_info_with, BuildInfo, DeviceInfo, HardwareInfo};
+pub use lifecycle::{webhook, LifecycleEvent, LifecycleHook, LifecycleStage};
+pub use media::{
+    GenericMediaMetadata, IdleReason, Image, Media, MediaCommands, MediaPosition, MediaStatus,
+    Metadata, MusicTrackMediaMetadata, PlayerState, StreamType,
+};
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+pub use mix::MixMode;
+#[cfg(feature = "mqtt")]
+pub use mqtt::Bridge;
+pub use multicast::SocketOptions;
+pub use notifier::{Notifier, Template};
+pub use options::ConnectOptions;
+pub use pick::{pick_best, Criteria};
+pub use policy::{Policy, QuietAction, QuietHours, RateLimit, SuppressReason};
+pub use pool::Pool;
+pub use priority::{Preemption, Priority};
+pub use probe::ProbeInfo;
+pub use registry::{CachedDevice, Registry};
+pub use resume::ResumePolicy;
+pub use retry::RetryPolicy;
+pub use scheduler::{Cron, JobHandle, Scheduler};
+pub use server::{LocalAudioServer, LocalTts};
+pub use session::Session;
+pub use settings::{ConnectionSettings, Timeouts};
+pub use status::{AppStatus, DeviceStatus};
+pub use subtitles::TextTrack;
+#[cfg(feature = "message-tap")]
+pub use tap::MessageTap;
+pub use text::{ExpandUnits, Lexicon, Pipeline, Preprocessor, SpellAcronyms, StripEmoji};
+pub use theme::Theme;
+pub use tone::{Melody, Note};
+pub use transcode::{Ffmpeg, Transcoder};
+pub use transport::{
+    AsyncCastDevice, AsyncTransport, CastMessage, Direction, Payload, NAMESPACE_CONNECTION,
+    NAMESPACE_HEARTBEAT, NAMESPACE_MEDIA, NAMESPACE_RECEIVER,
+};
+pub use tts::{Clip, Gender, GoogleTranslate, SayOptions, TtsProvider, Voice};
+pub use watch::{watch, DeviceEvent};
+pub use zone::Zones;
+
+pub const DEFAULT_DESTINATION_ID: &str = "receiver-0";
+const DEFAULT_LANGUAGE: &str = "en";
+const DEFAULT_MEDIA_RECEIVER: &str = "CC1AD845";
 const SERVICE_NAME: &'static str = "_googlecast._tcp.local";
+const QUERY_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_SEEN_TTL: Duration = Duration::from_secs(120);
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetKey {
+    Uuid(String),
+    Addr(SocketAddr),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Target {
     name: String,
     addr: SocketAddr,
+    model: Option<String>,
+    uuid: Option<String>,
+    capabilities: Capabilities,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    host: Option<String>,
 }
 
 pub struct Connection {
-    device: Arc<CastDevice<'static>>,
+    link: Arc<Link>,
+    session: Arc<Mutex<Option<Session>>>,
+    fallback: Option<ChimeSource>,
+    language: String,
+    provider: Arc<dyn TtsProvider>,
+    queue: Turns,
+    batch: Batch,
+    retry: RetryPolicy,
+    policy: Policy,
+    settings: ConnectionSettings,
+    server: Mutex<Option<LocalAudioServer>>,
+    preprocessor: Option<Arc<dyn Preprocessor>>,
+    app: Mutex<Option<AppStatus>>,
+    recent: Mutex<Recent>,
+    on_suppressed: Option<Arc<dyn Fn(&str, SuppressReason) + Send + Sync>>,
+    lifecycle: Option<LifecycleHook>,
+    closed: bool,
+}
+
+struct Utterance {
+    id: u64,
+    message: String,
+    language: String,
+    passthrough: bool,
+}
+
+struct Link {
+    id: DeviceId,
+    options: ConnectOptions,
+    capabilities: Capabilities,
+    device: Mutex<Device>,
+    health: Mutex<Tracker>,
+    commands: Turns,
+    #[cfg(feature = "message-tap")]
+    tap: tap::TapSlot,
 }
 
 impl Connection {
-    pub async fn say<'a, T: Into<Cow<'a, str>>>(&self, message: T) -> Result<(), Error> {
-        let device = self.device.clone();
-        let message = message.into().into_owned();
+    pub async fn say<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+    ) -> Result<AnnouncementHandle<'_>, Error> {
+        self.say_with_options(message, SayOptions::default()).await
+    }
+
+    pub async fn say_and_wait<'a, T: Into<Cow<'a, str>>>(&self, message: T) -> Result<(), Error> {
+        self.queue(message).await
+    }
+
+    pub async fn queue<'a, T: Into<Cow<'a, str>>>(&self, message: T) -> Result<(), Error> {
+        self.speak(message, SayOptions::default(), self.provider.clone(), true)
+            .await
+    }
+
+    pub async fn say_in<'a, 'b, T: Into<Cow<'a, str>>, L: Into<Cow<'b, str>>>(
+        &self,
+        message: T,
+        language: L,
+    ) -> Result<AnnouncementHandle<'_>, Error> {
+        let options = SayOptions {
+            lang: Some(language.into().into_owned()),
+            ..SayOptions::default()
+        };
+        self.say_with_options(message, options).await
+    }
+
+    pub async fn say_with<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        provider: Arc<dyn TtsProvider>,
+    ) -> Result<AnnouncementHandle<'_>, Error> {
+        self.announce(message, SayOptions::default(), provider)
+            .await
+    }
+
+    pub async fn say_with_priority<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        priority: Priority,
+    ) -> Result<AnnouncementHandle<'_>, Error> {
+        let options = SayOptions {
+            priority,
+            ..SayOptions::default()
+        };
+        self.say_with_options(message, options).await
+    }
+
+    pub async fn say_with_options<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        options: SayOptions,
+    ) -> Result<AnnouncementHandle<'_>, Error> {
+        self.announce(message, options, self.provider.clone()).await
+    }
+
+    async fn announce<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        options: SayOptions,
+        provider: Arc<dyn TtsProvider>,
+    ) -> Result<AnnouncementHandle<'_>, Error> {
+        let (utterance, admission) = self.prepare(message, &options, &provider).await?;
+        let ticket = Ticket::default();
+        let result = self
+            .dispatch(utterance, options, provider, false, &ticket)
+            .await;
+        admission.settle(&result);
+        result?;
+        Ok(AnnouncementHandle::new(self, &ticket))
+    }
+
+    async fn speak<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        options: SayOptions,
+        provider: Arc<dyn TtsProvider>,
+        wait: bool,
+    ) -> Result<(), Error> {
+        let (utterance, admission) = self.prepare(message, &options, &provider).await?;
+        let result = self
+            .dispatch(utterance, options, provider, wait, &Ticket::default())
+            .await;
+        admission.settle(&result);
+        result
+    }
+
+    async fn prepare<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        options: &SayOptions,
+        provider: &Arc<dyn TtsProvider>,
+    ) -> Result<(Utterance, Admission<'_>), Error> {
+        let (message, language, passthrough) = self.render(message, options, provider)?;
+        let admission = self.apply_policy(&message).await?;
+        let id = lifecycle::next_id();
+        self.emit(id, LifecycleStage::Queued, &message);
+        let utterance = Utterance {
+            id,
+            message,
+            language,
+            passthrough,
+        };
+        Ok((utterance, admission))
+    }
+
+    fn render<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        options: &SayOptions,
+        provider: &Arc<dyn TtsProvider>,
+    ) -> Result<(String, String, bool), Error> {
+        let mut message = message.into().into_owned();
+        let voices = match &options.voice {
+            Some(_) => provider.voices(),
+            None => Vec::new(),
+        };
+        let voice = match &options.voice {
+            Some(id) if !voices.is_empty() => Some(
+                voices
+                    .into_iter()
+                    .find(|voice| voice.id == *id)
+                    .ok_or_else(|| Error::UnknownVoice(id.clone()))?,
+            ),
+            _ => None,
+        };
+        let language = options
+            .lang
+            .clone()
+            .or_else(|| voice.map(|voice| voice.language));
+        #[cfg(feature = "detect-language")]
+        let language = language.or_else(|| {
+            self.settings
+                .detect_language
+                .then(|| detect::detect(&message))
+                .flatten()
+                .map(String::from)
+        });
+        let language = language.unwrap_or_else(|| self.language.clone());
+        let passthrough = options.ssml && provider.supports_ssml();
+        if options.ssml && !passthrough {
+            message = text::strip_ssml(&message);
+        }
+        if let (false, Some(preprocessor)) = (passthrough, &self.preprocessor) {
+            message = preprocessor.process(&message);
+        }
+        if message.trim().is_empty() {
+            return Err(Error::EmptyMessage);
+        }
+        Ok((message, language, passthrough))
+    }
+
+    async fn prefetch<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        options: &SayOptions,
+        provider: Arc<dyn TtsProvider>,
+    ) -> Result<(), Error> {
+        let (message, language, passthrough) = self.render(message, options, &provider)?;
+        let chunks = chunks(&message, passthrough, &*provider);
+        let options = options.clone();
         unblock(move || {
-            let app: CastDeviceApp = "CC1AD845".parse().unwrap();
-            let app = device.receiver.launch_app(&app)?;
-            device.connection.connect(&app.transport_id)?;
-            device.media.load(
-                &app.transport_id,
-                &app.session_id,
-                &Media {
-                    stream_type: StreamType::Buffered,
-                    duration: None,
-                    metadata: None,
-                    content_type: "audio/mp3".into(),
-                    content_id: url(&message, "en"),
-                },
-            )?;
-            Ok(())
+            chunks
+                .iter()
+                .try_for_each(|chunk| provider.synthesize(chunk, &language, &options).map(drop))
         })
         .await
     }
+
+    async fn dispatch(
+        &self,
+        mut utterance: Utterance,
+        options: SayOptions,
+        provider: Arc<dyn TtsProvider>,
+        wait: bool,
+        ticket: &Ticket,
+    ) -> Result<(), Error> {
+        let pending = self.pending(utterance.id, &utterance.message);
+        let (mut followers, mut turn) = (Vec::new(), None);
+        let batched = !utterance.passthrough && options.chime.is_none();
+        if self.settings.batch && batched && self.queue.busy() {
+            match self.batch.enter(&utterance.language, utterance.message) {
+                Slot::Alone(alone) => utterance.message = alone,
+                Slot::Follow(receiver) => {
+                    let result = batch::follow(receiver).await;
+                    pending.finish(&result, None);
+                    return result;
+                }
+                Slot::Lead(lead, first) => {
+                    turn = Some(self.queue.acquire(options.priority).await);
+                    let (messages, senders) = lead.finish();
+                    utterance.message = batch::join(std::iter::once(first).chain(messages));
+                    followers = senders;
+                }
+            }
+        }
+        let wait = wait || !followers.is_empty();
+        let result = self
+            .deliver(utterance, options, provider, wait, ticket, turn)
+            .await;
+        pending.finish(&result, ticket.media().filter(|_| !wait));
+        #[cfg(feature = "metrics")]
+        metrics::announcement(&self.link.id, &result);
+        batch::notify(followers, result)
+    }
+
+    async fn deliver(
+        &self,
+        utterance: Utterance,
+        options: SayOptions,
+        provider: Arc<dyn TtsProvider>,
+        wait: bool,
+        ticket: &Ticket,
+        turn: Option<Turn<'_>>,
+    ) -> Result<(), Error> {
+        let Utterance {
+            id,
+            message,
+            language,
+            passthrough,
+        } = utterance;
+        let chunks = chunks(&message, passthrough, &*provider);
+        let mut media: Vec<Box<dyn Fn() -> Result<Media, Error> + Send + Sync>> = Vec::new();
+        if let Some(chime) = &options.chime {
+            let chime = self.chime_clip(chime).await?.media();
+            media.push(Box::new(move || Ok(chime.clone())));
+        }
+        let metadata = options.metadata.clone().filter(|_| self.supports_video());
+        for chunk in chunks {
+            let provider = provider.clone();
+            let language = language.clone();
+            let options = options.clone();
+            let metadata = metadata.clone();
+            media.push(Box::new(move || {
+                #[cfg(feature = "metrics")]
+                let start = Instant::now();
+                let clip = provider.synthesize(&chunk, &language, &options)?;
+                #[cfg(feature = "metrics")]
+                metrics::tts_fetch(start.elapsed());
+                let mut media = clip.media();
+                media.metadata = metadata.clone();
+                Ok(media)
+            }));
+        }
+        let mut level = options.volume.or(self.settings.volume);
+        let previous = match (level, self.policy.min_volume) {
+            (None, None) => None,
+            _ => Some(self.volume().await?),
+        };
+        if let (Some(min), Some(previous)) = (self.policy.min_volume, previous) {
+            if level.unwrap_or(previous) < min {
+                level = Some(min);
+            }
+        }
+        let level = level.filter(|level| Some(*level) != previous);
+        let resume = options.resume.unwrap_or(self.settings.resume);
+        let playback = match options.mix {
+            MixMode::Replace => None,
+            MixMode::Interrupt => self.playback().await?,
+        };
+        let app = match (resume, &playback) {
+            (ResumePolicy::Keep, _) | (_, Some(_)) => None,
+            _ => self.foreground_app().await?,
+        };
+        if self.settings.wake {
+            self.wake().await?;
+        }
+        if let Some(level) = level {
+            self.set_volume(level).await?;
+        }
+        let wait = wait || level.is_some() || resume != ResumePolicy::Keep || playback.is_some();
+        let result = self
+            .enqueue(
+                media,
+                wait,
+                options.priority,
+                options.preemption,
+                Some((id, message.as_str())),
+                ticket,
+                turn,
+            )
+            .await;
+        if let (Some(_), Some(previous)) = (level, previous) {
+            self.set_volume(previous).await?;
+        }
+        result?;
+        match playback {
+            Some(playback) => self.resume_playback(playback).await,
+            None => self.restore(app, resume).await,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn enqueue<F>(
+        &self,
+        media: Vec<F>,
+        wait: bool,
+        priority: Priority,
+        preemption: Preemption,
+        announcement: Option<(u64, &str)>,
+        ticket: &Ticket,
+        held: Option<Turn<'_>>,
+    ) -> Result<(), Error>
+    where
+        F: Fn() -> Result<Media, Error> + Send + Sync + 'static,
+    {
+        if priority == Priority::Urgent && preemption == Preemption::Immediate && self.queue.busy()
+        {
+            let _ = self.stop().await;
+        }
+        let mut turn = match held {
+            Some(turn) => turn,
+            None => self.queue.acquire(priority).await,
+        };
+        for (i, media) in media.into_iter().enumerate() {
+            self.wait_idle().await?;
+            if self.queue.outranked(priority) {
+                drop(turn);
+                turn = self.queue.acquire(priority).await;
+                self.wait_idle().await?;
+            }
+            ticket.loaded(self.load_media(media).await?);
+            if let (0, Some((id, message))) = (i, announcement) {
+                self.emit(id, LifecycleStage::Started, message);
+            }
+        }
+        if wait {
+            self.wait_idle().await?;
+        }
+        Ok(())
+    }
+
+    async fn load_media<F>(&self, media: F) -> Result<Session, Error>
+    where
+        F: Fn() -> Result<Media, Error> + Send + Sync + 'static,
+    {
+        let id = &self.link.id;
+        let timeout = self.settings.timeouts.load;
+        let reuse = self.settings.reuse_session;
+        let result = match unblock(media).await {
+            Ok(media) => {
+                let media = &media;
+                self.run_once("load", timeout, move |device| async move {
+                    load(&device, id, media, reuse).await
+                })
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Ok(session) = &result {
+            *self.session.lock().unwrap() = Some(session.clone());
+        }
+        let fallback = match &self.fallback {
+            Some(source) => Some(source.clone()),
+            None => self.settings.fallback.then_some(ChimeSource::Tone),
+        };
+        if let (Err(_), Some(source)) = (&result, fallback) {
+            if let Ok(clip) = self.chime_clip(&source).await {
+                let media = &clip.media();
+                let fallback = self
+                    .run_once("load", timeout, move |device| async move {
+                        load(&device, id, media, reuse).await
+                    })
+                    .await;
+                if let Ok(session) = fallback {
+                    *self.session.lock().unwrap() = Some(session);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn set_language<T: Into<String>>(&mut self, language: T) {
+        self.language = language.into();
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn device_id(&self) -> &DeviceId {
+        &self.link.id
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.link.capabilities
+    }
+
+    fn supports_video(&self) -> bool {
+        self.link.capabilities.supports_video()
+    }
+
+    pub fn set_fallback(&mut self, fallback: Option<ChimeSource>) {
+        self.fallback = fallback;
+    }
+
+    pub fn set_preprocessor(&mut self, preprocessor: Option<Arc<dyn Preprocessor>>) {
+        self.preprocessor = preprocessor;
+    }
+
+    pub fn add_preprocessor<P: Preprocessor + 'static>(&mut self, preprocessor: P) {
+        let mut pipeline = Pipeline::new();
+        if let Some(existing) = self.preprocessor.take() {
+            pipeline.push(existing);
+        }
+        self.preprocessor = Some(Arc::new(pipeline.then(preprocessor)));
+    }
+
+    pub fn set_provider(&mut self, provider: Arc<dyn TtsProvider>) {
+        self.provider = provider;
+    }
+
+    fn device(&self) -> Device {
+        self.link.device()
+    }
+}
+
+fn chunks(message: &str, passthrough: bool, provider: &dyn TtsProvider) -> Vec<String> {
+    match provider.max_len() {
+        Some(max) if !passthrough => tts::chunks(message, max),
+        _ => vec![message.to_owned()],
+    }
+}
+
+async fn load(
+    device: &Device,
+    id: &DeviceId,
+    media: &Media,
+    reuse: bool,
+) -> Result<Session, Error> {
+    let (app, launched) = session::media_receiver(device, id, reuse).await?;
+    log!(debug, device = %id, transport_id = %app.transport_id, session_id = %app.session_id, "launched media receiver");
+    device.connect(&app.transport_id).await?;
+    let status = device
+        .load(&app.transport_id, &app.session_id, media)
+        .await
+        .map_err(|source| Error::LoadMedia {
+            device: id.clone(),
+            source: Box::new(source),
+        })?;
+    let media_session_id = status.first().map(|status| status.media_session_id);
+    log!(debug, device = %id, content_id = %media.content_id, media_session_id = ?media_session_id, "loaded media");
+    Ok(Session {
+        media_session_id,
+        transport_id: app.transport_id,
+        session_id: app.session_id,
+        launched,
+    })
+}
+
+async fn with_timeout<T, F: Future<Output = Result<T, Error>>>(
+    duration: Option<Duration>,
+    device: &DeviceId,
+    operation: &'static str,
+    future: F,
+) -> Result<T, Error> {
+    let duration = match duration {
+        Some(duration) => duration,
+        None => return future.await,
+    };
+    pin_mut!(future);
+    match select(future, Timer::after(duration)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(Error::Timeout {
+            operation,
+            device: device.clone(),
+        }),
+    }
 }
 
 impl Target {
     pub async fn connect(self) -> Result<Connection, Error> {
-        Ok(Connection {
-            device: Arc::new(
-                unblock(move || {
-                    let device = CastDevice::connect_without_host_verification(
-                        self.addr.ip().to_string(),
-                        self.addr.port(),
-                    )?;
-                    device
-                        .connection
-                        .connect(DEFAULT_DESTINATION_ID.to_string())?;
-                    device.heartbeat.ping()?;
-                    Ok::<_, Error>(device)
-                })
-                .await?,
-            ),
-        })
+        self.connect_with(ConnectOptions::default()).await
+    }
+
+    pub async fn connect_with(self, options: ConnectOptions) -> Result<Connection, Error> {
+        let addr = self.addr;
+        let capabilities = self.capabilities;
+        let id = DeviceId {
+            name: self.name,
+            addr,
+        };
+        let mut attempt = 0;
+        let device = loop {
+            log!(debug, device = %id, attempt, "connecting");
+            #[cfg(feature = "metrics")]
+            let start = Instant::now();
+            let result = with_timeout(
+                options.timeout,
+                &id,
+                "connect",
+                Device::open(addr, &options),
+            )
+            .await
+            .map_err(|e| e.on(&id, |device, source| Error::Connect { device, source }));
+            #[cfg(feature = "tracing")]
+            if let Err(e) = &result {
+                tracing::warn!(device = %id, attempt, error = %e, "connect failed");
+            }
+            #[cfg(feature = "metrics")]
+            if result.is_ok() {
+                metrics::connected(start.elapsed());
+            }
+            match result {
+                Err(_) if attempt < options.retry.attempts => {
+                    attempt += 1;
+                    Timer::after(options.retry.delay(attempt)).await;
+                }
+                result => break result?,
+            }
+        };
+        log!(info, device = %id, "connected");
+        #[cfg(feature = "message-tap")]
+        let tap = tap::TapSlot::default();
+        #[cfg(feature = "message-tap")]
+        device.forward_to(&tap).await;
+        let heartbeat = options.heartbeat;
+        let retry = options.retry;
+        let connection = Connection {
+            link: Arc::new(Link {
+                id,
+                options,
+                capabilities,
+                device: Mutex::new(device),
+                health: Mutex::new(Tracker::default()),
+                commands: Turns::default(),
+                #[cfg(feature = "message-tap")]
+                tap,
+            }),
+            session: Arc::new(Mutex::new(None)),
+            fallback: None,
+            language: DEFAULT_LANGUAGE.into(),
+            provider: Arc::new(GoogleTranslate),
+            queue: Turns::default(),
+            batch: Batch::default(),
+            retry,
+            policy: Policy::default(),
+            settings: ConnectionSettings::default(),
+            server: Mutex::new(None),
+            preprocessor: None,
+            app: Mutex::new(None),
+            recent: Mutex::new(Recent::default()),
+            on_suppressed: None,
+            lifecycle: None,
+            closed: false,
+        };
+        if let Some(interval) = heartbeat {
+            connection.spawn_heartbeat(interval, |_| {});
+        }
+        Ok(connection)
     }
 }
 
 impl Target {
+    pub fn new<T: Into<String>>(name: T, addr: SocketAddr) -> Self {
+        Target {
+            name: name.into(),
+            addr,
+            model: None,
+            uuid: None,
+            capabilities: Capabilities::default(),
+            host: None,
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn key(&self) -> TargetKey {
+        match &self.uuid {
+            Some(uuid) => TargetKey::Uuid(filter::normalize_uuid(uuid)),
+            None => TargetKey::Addr(self.addr),
+        }
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    pub fn uuid(&self) -> Option<&str> {
+        self.uuid.as_deref()
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    pub fn supports_video(&self) -> bool {
+        self.capabilities.supports_video()
+    }
+
+    pub fn supports_multizone(&self) -> bool {
+        self.capabilities.multizone_group()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceId {
+    pub name: String,
+    pub addr: SocketAddr,
+}
+
+impl fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.addr)
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("mdns error: {0}")]
-    Mdns(#[from] mdns::Error),
-    #[error("chromecast error: {0}")]
-    Cast(#[from] rust_cast::errors::Error),
+    #[error("discovery failed: {0}")]
+    Discovery(#[from] mdns::Error),
+    #[error("failed to connect to {device}: {source}")]
+    Connect {
+        device: DeviceId,
+        source: Box<Error>,
+    },
+    #[error("failed to launch app on {device}: {source}")]
+    LaunchApp {
+        device: DeviceId,
+        source: Box<Error>,
+    },
+    #[error("failed to load media on {device}: {source}")]
+    LoadMedia {
+        device: DeviceId,
+        source: Box<Error>,
+    },
+    #[error("command failed on {device}: {source}")]
+    Command {
+        device: DeviceId,
+        source: Box<Error>,
+    },
+    #[error("lost connection to {device}: {source}")]
+    Disconnected {
+        device: DeviceId,
+        source: Box<Error>,
+    },
+    #[error("{operation} timed out on {device}")]
+    Timeout {
+        operation: &'static str,
+        device: DeviceId,
+    },
+    #[error("{device} did not wake from standby")]
+    WakeFailed { device: DeviceId },
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "serialize")]
+    #[error("invalid config: {0}")]
+    Config(#[from] toml::de::Error),
+    #[error("no device matching {0:?} was found")]
+    DeviceNotFound(String),
+    #[error("no cast devices were discovered")]
+    NoDevices,
+    #[error("no zone named {0:?}")]
+    UnknownZone(String),
+    #[error("no template named {0:?}")]
+    UnknownTemplate(String),
+    #[error("template variable {0:?} was not provided")]
+    MissingVariable(String),
+    #[error("malformed template {0:?}")]
+    InvalidTemplate(String),
+    #[error("voice {0:?} is not offered by the tts provider")]
+    UnknownVoice(String),
+    #[error("announcement message is empty")]
+    EmptyMessage,
+    #[error("operation was cancelled")]
+    Cancelled,
+    #[error("blocking call made from inside an async runtime")]
+    InsideRuntime,
+    #[error("batched announcement failed: {0}")]
+    Batched(Arc<Error>),
+    #[error("transcoding failed: {0}")]
+    Transcode(String),
+    #[error("invalid schedule {0:?}")]
+    InvalidSchedule(String),
+    #[error("invalid {0} record")]
+    InvalidRecord(&'static str),
+    #[error("http error: {0}")]
+    Http(#[from] Box<ureq::Error>),
+    #[error("device info is unavailable")]
+    InfoUnavailable,
+    #[error("no media is active on this connection")]
+    NoActiveMedia,
+    #[error("no app has been launched on this connection")]
+    NoActiveApp,
+    #[error("the active media does not support {0}")]
+    UnsupportedFeature(&'static str),
+    #[error("{0} is not supported by this device")]
+    Unsupported(&'static str),
+    #[error("announcement suppressed by policy: {0:?}")]
+    Suppressed(SuppressReason),
+    #[error("invalid request: {0}")]
+    InvalidRequest(&'static str),
+    #[error("protocol error: {0}")]
+    Protocol(&'static str),
+    #[error("device rejected the request: {0}")]
+    Rejected(String),
+    #[error("tls verification was requested without a root certificate")]
+    MissingRootCertificate,
+    #[error("tls error: {0}")]
+    Tls(#[from] async_native_tls::Error),
+    #[cfg(feature = "dns-sd")]
+    #[error("dns-sd error: {0}")]
+    DnsSd(#[from] zeroconf::error::Error),
+    #[cfg(feature = "mqtt")]
+    #[error("mqtt error: {0}")]
+    Mqtt(#[from] rumqttc::ClientError),
+    #[cfg(all(target_os = "linux", feature = "desktop-notifications"))]
+    #[error("d-bus error: {0}")]
+    DBus(#[from] dbus::Error),
+}
+
+impl Error {
+    pub fn device(&self) -> Option<&DeviceId> {
+        match self {
+            Error::Connect { device, .. }
+            | Error::LaunchApp { device, .. }
+            | Error::LoadMedia { device, .. }
+            | Error::Command { device, .. }
+            | Error::Disconnected { device, .. }
+            | Error::Timeout { device, .. }
+            | Error::WakeFailed { device } => Some(device),
+            _ => None,
+        }
+    }
+
+    fn is_connection_error(&self) -> bool {
+        match self {
+            Error::Io(_) | Error::Tls(_) => true,
+            Error::LaunchApp { source, .. }
+            | Error::LoadMedia { source, .. }
+            | Error::Command { source, .. } => source.is_connection_error(),
+            Error::Batched(source) => source.is_connection_error(),
+            _ => false,
+        }
+    }
+
+    fn on(self, device: &DeviceId, kind: fn(DeviceId, Box<Error>) -> Error) -> Self {
+        match self {
+            error @ (Error::Io(_)
+            | Error::Tls(_)
+            | Error::Json(_)
+            | Error::Protocol(_)
+            | Error::Rejected(_)) => kind(device.clone(), Box::new(error)),
+            other => other,
+        }
+    }
 }
 
 #[pin_project]
-struct Unique<T: Stream<Item = Result<Target, Error>>> {
+struct Unique<T: Stream<Item = Result<Target, Error>>, F, K> {
     #[pin]
     stream: T,
-    seen: HashSet<SocketAddr>,
+    key: F,
+    ttl: Duration,
+    seen: HashMap<K, Instant>,
 }
 
-impl<T: Stream<Item = Result<Target, Error>>> Unique<T> {
-    fn new(stream: T) -> Self {
+impl<T: Stream<Item = Result<Target, Error>>, F: FnMut(&Target) -> K, K: Hash + Eq>
+    Unique<T, F, K>
+{
+    fn new(stream: T, key: F, ttl: Duration) -> Self {
         Unique {
             stream,
-            seen: HashSet::new(),
+            key,
+            ttl,
+            seen: HashMap::new(),
         }
     }
 }
 
-impl<T: Stream<Item = Result<Target, Error>>> Stream for Unique<T> {
+impl<T: Stream<Item = Result<Target, Error>>, F: FnMut(&Target) -> K, K: Hash + Eq> Stream
+    for Unique<T, F, K>
+{
     type Item = T::Item;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
         let mut stream = this.stream;
+        let key = this.key;
+        let ttl = *this.ttl;
         let seen = this.seen;
         loop {
             let item = match ready!(stream.as_mut().poll_next(cx)) {
                 Some(item) => item,
                 None => return Poll::Ready(None),
             }?;
-            if seen.insert(item.addr.clone()) {
+            let now = Instant::now();
+            seen.retain(|_, at| now.duration_since(*at) < ttl);
+            if seen.insert(key(&item), now).is_none() {
+                #[cfg(feature = "metrics")]
+                metrics::discovered();
                 return Poll::Ready(Some(Ok(item)));
             }
         }
     }
 }
 
-pub fn discover() -> impl Stream<Item = Result<Target, Error>> {
-    Unique::new(
-        async move {
-            match mdns::discover::all(SERVICE_NAME, Duration::from_secs(5)) {
-                Ok(stream) => Either::Left(stream.listen().map_err(Error::Mdns)),
-                Err(e) => Either::Right(once(ready(Err(e.into())))),
+#[pin_project]
+struct Limit<T: Stream<Item = Result<Target, Error>>> {
+    #[pin]
+    stream: T,
+    remaining: usize,
+}
+
+impl<T: Stream<Item = Result<Target, Error>>> Stream for Limit<T> {
+    type Item = T::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if *this.remaining == 0 {
+            return Poll::Ready(None);
+        }
+        let item = ready!(this.stream.poll_next(cx));
+        if let Some(Ok(_)) = item {
+            *this.remaining -= 1;
+        }
+        Poll::Ready(item)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddrPreference {
+    #[default]
+    V4,
+    V6,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiscoveryBackend {
+    #[default]
+    Mdns,
+    #[cfg(feature = "dns-sd")]
+    DnsSd,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscoverOptions {
+    preference: AddrPreference,
+    filter: DiscoveryFilter,
+    interfaces: Vec<Ipv4Addr>,
+    backend: DiscoveryBackend,
+    seen_ttl: Duration,
+    socket: Option<SocketOptions>,
+}
+
+impl Default for DiscoverOptions {
+    fn default() -> Self {
+        DiscoverOptions {
+            preference: AddrPreference::default(),
+            filter: DiscoveryFilter::default(),
+            interfaces: Vec::new(),
+            backend: DiscoveryBackend::default(),
+            seen_ttl: DEFAULT_SEEN_TTL,
+            socket: None,
+        }
+    }
+}
+
+impl DiscoverOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn prefer(mut self, preference: AddrPreference) -> Self {
+        self.preference = preference;
+        self
+    }
+
+    pub fn filter(mut self, filter: DiscoveryFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn interfaces<I: IntoIterator<Item = Ipv4Addr>>(mut self, interfaces: I) -> Self {
+        self.interfaces = interfaces.into_iter().collect();
+        self
+    }
+
+    pub fn backend(mut self, backend: DiscoveryBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn seen_ttl(mut self, ttl: Duration) -> Self {
+        self.seen_ttl = ttl;
+        self
+    }
+
+    pub fn socket(mut self, options: SocketOptions) -> Self {
+        self.socket = Some(options);
+        self
+    }
+
+    pub fn discover(self) -> impl Stream<Item = Result<Target, Error>> {
+        self.discover_by(Target::key)
+    }
+
+    pub fn discover_resilient<F: FnMut(Error)>(
+        self,
+        mut on_error: F,
+    ) -> impl Stream<Item = Target> {
+        self.discover().filter_map(move |result| {
+            ready(match result {
+                Ok(target) => Some(target),
+                Err(e) => {
+                    on_error(e);
+                    None
+                }
+            })
+        })
+    }
+
+    #[cfg_attr(not(feature = "dns-sd"), allow(clippy::let_and_return))]
+    pub fn discover_by<F: FnMut(&Target) -> K, K: Hash + Eq>(
+        self,
+        key: F,
+    ) -> impl Stream<Item = Result<Target, Error>> {
+        let ttl = self.seen_ttl;
+        match self.backend {
+            DiscoveryBackend::Mdns => {}
+            #[cfg(feature = "dns-sd")]
+            DiscoveryBackend::DnsSd => {
+                return Either::Right(Unique::new(
+                    dnssd::browse(SERVICE_NAME).try_filter_map(move |service| {
+                        let txt = service
+                            .txt
+                            .iter()
+                            .map(|(key, value)| (key.as_str(), value.as_str()))
+                            .collect();
+                        let (v4, v6) = match service.ip {
+                            IpAddr::V4(_) => (Some(service.ip), None),
+                            IpAddr::V6(_) => (None, Some(service.ip)),
+                        };
+                        ready(Ok(resolve(&txt, v4, v6, service.port, service.host, &self)))
+                    }),
+                    key,
+                    ttl,
+                ))
             }
         }
-        .into_stream()
-        .flatten()
-        .try_filter_map(|response| async move {
-            response
-                .additional
+        let stream = Unique::new(
+            responses(&self).try_filter_map(move |response| ready(Ok(parse(&response, &self)))),
+            key,
+            ttl,
+        );
+        #[cfg(feature = "dns-sd")]
+        let stream = Either::Left(stream);
+        stream
+    }
+}
+
+fn responses(options: &DiscoverOptions) -> impl Stream<Item = Result<mdns::Response, Error>> {
+    match options.socket {
+        Some(socket) => Either::Left(multicast::responses(&options.interfaces, socket)),
+        None => Either::Right(mdns_responses(&options.interfaces)),
+    }
+}
+
+fn mdns_responses(interfaces: &[Ipv4Addr]) -> impl Stream<Item = Result<mdns::Response, Error>> {
+    if interfaces.is_empty() {
+        Either::Left(query(None))
+    } else {
+        Either::Right(select_all(
+            interfaces
                 .iter()
-                .filter_map(|record| {
-                    if let RecordKind::TXT(data) = &record.kind {
-                        data.into_iter()
-                            .filter_map(|item| {
-                                if let "fn" = item.split('=').next()? {
-                                    Some(item)
-                                } else {
-                                    None
-                                }
-                            })
-                            .map(|item| item.split('=').skip(1).next().map(String::from))
-                            .next()
-                            .flatten()
-                    } else {
-                        None
-                    }
-                })
-                .next()
-                .map(|name| {
-                    Some(Ok(Target {
-                        name,
-                        addr: SocketAddr::from((
-                            response
-                                .additional
-                                .iter()
-                                .filter_map(|item| {
-                                    if let RecordKind::A(ip) = item.kind {
-                                        Some(ip)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .next()?,
-                            response
-                                .additional
-                                .into_iter()
-                                .filter_map(|item| {
-                                    if let RecordKind::SRV { port, .. } = item.kind {
-                                        Some(port)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .next()?,
-                        )),
-                    }))
-                })
-                .flatten()
-                .transpose()
-        }),
-    )
+                .map(|&interface| Box::pin(query(Some(interface)))),
+        ))
+    }
+}
+
+fn query(interface: Option<Ipv4Addr>) -> impl Stream<Item = Result<mdns::Response, Error>> {
+    async move {
+        let discovery = match interface {
+            Some(interface) => mdns::discover::interface(SERVICE_NAME, QUERY_INTERVAL, interface),
+            None => mdns::discover::all(SERVICE_NAME, QUERY_INTERVAL),
+        };
+        match discovery {
+            Ok(stream) => Either::Left(stream.listen().map_err(Error::Discovery)),
+            Err(e) => Either::Right(once(ready(Err(e.into())))),
+        }
+    }
+    .into_stream()
+    .flatten()
+}
+
+pub fn discover() -> impl Stream<Item = Result<Target, Error>> {
+    DiscoverOptions::default().discover()
+}
+
+pub fn discover_resilient() -> impl Stream<Item = Target> {
+    DiscoverOptions::default().discover_resilient(|_e| {
+        log!(warn, error = %_e, "discarding malformed discovery response");
+    })
+}
+
+pub fn discover_named<T: Into<String>>(pattern: T) -> impl Stream<Item = Result<Target, Error>> {
+    DiscoverOptions::default()
+        .filter(DiscoveryFilter::new().name(pattern))
+        .discover()
+}
+
+pub fn discover_on<I: IntoIterator<Item = Ipv4Addr>>(
+    interfaces: I,
+) -> impl Stream<Item = Result<Target, Error>> {
+    DiscoverOptions::default().interfaces(interfaces).discover()
+}
+
+pub fn discover_for(duration: Duration) -> impl Stream<Item = Result<Target, Error>> {
+    discover().take_until(Timer::after(duration))
+}
+
+pub fn discover_n(count: usize) -> impl Stream<Item = Result<Target, Error>> {
+    Limit {
+        stream: discover(),
+        remaining: count,
+    }
+}
+
+pub fn discover_by<F: FnMut(&Target) -> K, K: Hash + Eq>(
+    key: F,
+) -> impl Stream<Item = Result<Target, Error>> {
+    DiscoverOptions::default().discover_by(key)
+}
+
+fn parse(response: &mdns::Response, options: &DiscoverOptions) -> Option<Target> {
+    let txt: HashMap<&str, &str> = response
+        .additional
+        .iter()
+        .filter_map(|record| {
+            if let RecordKind::TXT(data) = &record.kind {
+                Some(data)
+            } else {
+                None
+            }
+        })
+        .flatten()
+        .filter_map(|item| item.split_once('='))
+        .collect();
+    let v4 = response
+        .additional
+        .iter()
+        .filter_map(|item| {
+            if let RecordKind::A(ip) = item.kind {
+                Some(IpAddr::V4(ip))
+            } else {
+                None
+            }
+        })
+        .next();
+    let v6 = response
+        .additional
+        .iter()
+        .filter_map(|item| {
+            if let RecordKind::AAAA(ip) = item.kind {
+                Some(IpAddr::V6(ip))
+            } else {
+                None
+            }
+        })
+        .next();
+    let (port, host) = response
+        .additional
+        .iter()
+        .filter_map(|item| {
+            if let RecordKind::SRV { port, target, .. } = &item.kind {
+                Some((*port, target.clone()))
+            } else {
+                None
+            }
+        })
+        .next()?;
+    resolve(&txt, v4, v6, port, Some(host), options)
+}
+
+fn resolve(
+    txt: &HashMap<&str, &str>,
+    v4: Option<IpAddr>,
+    v6: Option<IpAddr>,
+    port: u16,
+    host: Option<String>,
+    options: &DiscoverOptions,
+) -> Option<Target> {
+    let name = txt.get("fn")?.to_string();
+    if !options
+        .filter
+        .matches(&name, txt.get("md").copied(), txt.get("id").copied())
+    {
+        return None;
+    }
+    let ip = match options.preference {
+        AddrPreference::V4 => v4.or(v6),
+        AddrPreference::V6 => v6.or(v4),
+    }?;
+    let target = Target {
+        name,
+        addr: SocketAddr::new(ip, port),
+        model: txt.get("md").map(|model| model.to_string()),
+        uuid: txt.get("id").map(|uuid| uuid.to_string()),
+        capabilities: Capabilities::from_bits(
+            txt.get("ca")
+                .and_then(|bits| bits.parse().ok())
+                .unwrap_or_default(),
+        ),
+        host,
+    };
+    log!(trace, name = %target.name, addr = %target.addr, uuid = ?target.uuid, "discovered device");
+    Some(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        thread,
+    };
+
+    use async_io::block_on;
+    use futures::stream;
+
+    use super::*;
+
+    fn target(name: &str) -> Target {
+        Target::new(name, SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 8009))
+    }
+
+    fn names(items: Vec<(Result<Target, Error>, u64)>, ttl: Duration) -> Vec<String> {
+        let items = stream::iter(items).map(|(item, delay)| {
+            thread::sleep(Duration::from_millis(delay));
+            item
+        });
+        let unique = Unique::new(items, |target: &Target| target.name.clone(), ttl);
+        block_on(unique.collect::<Vec<_>>())
+            .into_iter()
+            .map(|item| item.map_or_else(|e| e.to_string(), |target| target.name))
+            .collect()
+    }
+
+    #[test]
+    fn unique_drops_repeats_within_ttl() {
+        let items = vec![
+            (Ok(target("a")), 0),
+            (Ok(target("a")), 0),
+            (Ok(target("b")), 0),
+            (Ok(target("b")), 0),
+        ];
+        assert_eq!(names(items, Duration::from_secs(60)), ["a", "b"]);
+    }
+
+    #[test]
+    fn unique_forgets_targets_after_ttl() {
+        let items = vec![
+            (Ok(target("a")), 0),
+            (Ok(target("a")), 0),
+            (Ok(target("a")), 100),
+        ];
+        assert_eq!(names(items, Duration::from_millis(30)), ["a", "a"]);
+    }
+
+    #[test]
+    fn unique_passes_errors_through() {
+        let items = vec![
+            (Ok(target("a")), 0),
+            (Err(Error::NoDevices), 0),
+            (Ok(target("a")), 0),
+        ];
+        assert_eq!(
+            names(items, Duration::from_secs(60)),
+            ["a".to_owned(), Error::NoDevices.to_string()]
+        );
+    }
 }
@@ -0,0 +1,216 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use async_io::Timer;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use crate::{
+    announcement::{settled, STATUS_POLL_INTERVAL},
+    runtime::spawn,
+    Connection, DeviceId, Error, Outcome,
+};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub type LifecycleHook = Arc<dyn Fn(&LifecycleEvent) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "serialize", serde(rename_all = "lowercase"))]
+pub enum LifecycleStage {
+    Queued,
+    Started,
+    Finished,
+    Failed,
+}
+
+impl LifecycleStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            LifecycleStage::Queued => "queued",
+            LifecycleStage::Started => "started",
+            LifecycleStage::Finished => "finished",
+            LifecycleStage::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+pub struct LifecycleEvent {
+    pub id: u64,
+    pub stage: LifecycleStage,
+    pub timestamp: DateTime<Utc>,
+    pub device: String,
+    pub message: String,
+    #[cfg_attr(feature = "serialize", serde(skip_serializing_if = "Option::is_none"))]
+    pub error: Option<String>,
+}
+
+impl LifecycleEvent {
+    pub fn new<T: Into<String>>(
+        id: u64,
+        stage: LifecycleStage,
+        device: &DeviceId,
+        message: T,
+    ) -> Self {
+        LifecycleEvent {
+            id,
+            stage,
+            timestamp: Utc::now(),
+            device: device.name.clone(),
+            message: message.into(),
+            error: None,
+        }
+    }
+
+    fn failed(id: u64, device: &DeviceId, message: &str, error: &Error) -> Self {
+        LifecycleEvent {
+            error: Some(error.to_string()),
+            ..LifecycleEvent::new(id, LifecycleStage::Failed, device, message)
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let mut payload = json!({
+            "id": self.id,
+            "stage": self.stage.as_str(),
+            "timestamp": self.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            "device": self.device,
+            "message": self.message,
+        });
+        if let Some(error) = &self.error {
+            payload["error"] = json!(error);
+        }
+        payload
+    }
+}
+
+pub(crate) fn next_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+pub fn webhook<T: Into<String>>(url: T) -> LifecycleHook {
+    let url = url.into();
+    let (sender, receiver) = mpsc::channel::<Value>();
+    thread::spawn(move || {
+        for payload in receiver {
+            if let Err(_e) = ureq::post(&url).timeout(WEBHOOK_TIMEOUT).send_json(payload) {
+                log!(warn, url = %url, error = %_e, "lifecycle webhook failed");
+            }
+        }
+    });
+    let sender = Mutex::new(sender);
+    Arc::new(move |event| {
+        let _ = sender.lock().unwrap().send(event.to_json());
+    })
+}
+
+impl Connection {
+    pub fn on_lifecycle<F: Fn(&LifecycleEvent) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.lifecycle = Some(Arc::new(hook));
+    }
+
+    pub fn set_webhook<T: Into<String>>(&mut self, url: T) {
+        self.lifecycle = Some(webhook(url));
+    }
+
+    pub fn clear_lifecycle_hook(&mut self) {
+        self.lifecycle = None;
+    }
+
+    pub(crate) fn emit(&self, id: u64, stage: LifecycleStage, message: &str) {
+        if let Some(hook) = &self.lifecycle {
+            hook(&LifecycleEvent::new(id, stage, &self.link.id, message));
+        }
+    }
+
+    pub(crate) fn pending(&self, id: u64, message: &str) -> Pending<'_> {
+        Pending {
+            connection: self,
+            id,
+            message: self.lifecycle.as_ref().map(|_| message.to_owned()),
+        }
+    }
+
+    fn report(&self, id: u64, message: &str, result: &Result<(), Error>) {
+        match (&self.lifecycle, result) {
+            (Some(hook), Err(e)) => hook(&LifecycleEvent::failed(id, &self.link.id, message, e)),
+            (Some(_), Ok(())) => self.emit(id, LifecycleStage::Finished, message),
+            (None, _) => {}
+        }
+    }
+
+    /// Emits `Finished` or `Failed` once the clip that is still playing goes idle, without
+    /// holding up the caller that queued it.
+    fn watch(&self, id: u64, message: String, (transport_id, media_session_id): (String, i32)) {
+        let (hook, link) = match &self.lifecycle {
+            Some(hook) => (hook.clone(), self.link.clone()),
+            None => return,
+        };
+        spawn(async move {
+            let transport_id = &transport_id;
+            let outcome = loop {
+                let statuses = link
+                    .command(|device| async move {
+                        device
+                            .media_status(transport_id, Some(media_session_id))
+                            .await
+                    })
+                    .await;
+                match statuses.map(|statuses| settled(statuses, media_session_id)) {
+                    Ok(Some(outcome)) => break Ok(outcome),
+                    Ok(None) => Timer::after(STATUS_POLL_INTERVAL).await,
+                    Err(e) => break Err(e),
+                }
+            };
+            let event = match outcome {
+                Ok(Outcome::Failed) => {
+                    let error = Error::Protocol("playback failed");
+                    LifecycleEvent::failed(id, &link.id, &message, &error)
+                }
+                Ok(_) => LifecycleEvent::new(id, LifecycleStage::Finished, &link.id, message),
+                Err(e) => LifecycleEvent::failed(id, &link.id, &message, &e),
+            };
+            hook(&event);
+        });
+    }
+}
+
+/// Owes the terminal event for an announcement that has emitted `Queued`. If the delivery is
+/// dropped before `finish`, the announcement is reported as cancelled.
+pub(crate) struct Pending<'a> {
+    connection: &'a Connection,
+    id: u64,
+    message: Option<String>,
+}
+
+impl Pending<'_> {
+    /// `playing` is the clip the delivery loaded but did not wait for, if any.
+    pub(crate) fn finish(mut self, result: &Result<(), Error>, playing: Option<(String, i32)>) {
+        match (self.message.take(), result, playing) {
+            (Some(message), Ok(()), Some(playing)) => {
+                self.connection.watch(self.id, message, playing)
+            }
+            (Some(message), _, _) => self.connection.report(self.id, &message, result),
+            (None, _, _) => {}
+        }
+    }
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        if let Some(message) = self.message.take() {
+            self.connection
+                .report(self.id, &message, &Err(Error::Cancelled));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use async_io::Timer;
+use serde_json::json;
+
+pub use rust_cast::channels::media::{
+    GenericMediaMetadata, IdleReason, Image, Media, Metadata, MusicTrackMediaMetadata, PlayerState,
+    StreamType,
+};
+
+use crate::{announcement::Ticket, Connection, Error, Preemption, Priority};
+
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MediaCommands(u32);
+
+impl MediaCommands {
+    const PAUSE: u32 = 1;
+    const SEEK: u32 = 2;
+    const STREAM_VOLUME: u32 = 4;
+    const STREAM_MUTE: u32 = 8;
+    const QUEUE_NEXT: u32 = 64;
+    const QUEUE_PREV: u32 = 128;
+
+    pub fn from_bits(bits: u32) -> Self {
+        MediaCommands(bits)
+    }
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub fn can_pause(self) -> bool {
+        self.0 & Self::PAUSE != 0
+    }
+
+    pub fn can_seek(self) -> bool {
+        self.0 & Self::SEEK != 0
+    }
+
+    pub fn can_set_stream_volume(self) -> bool {
+        self.0 & Self::STREAM_VOLUME != 0
+    }
+
+    pub fn can_mute_stream(self) -> bool {
+        self.0 & Self::STREAM_MUTE != 0
+    }
+
+    pub fn can_queue_next(self) -> bool {
+        self.0 & Self::QUEUE_NEXT != 0
+    }
+
+    pub fn can_queue_prev(self) -> bool {
+        self.0 & Self::QUEUE_PREV != 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MediaStatus {
+    pub media_session_id: i32,
+    pub player_state: PlayerState,
+    pub idle_reason: Option<IdleReason>,
+    pub current_time: Option<f32>,
+    pub duration: Option<f32>,
+    pub commands: MediaCommands,
+}
+
+#[derive(Debug, Clone)]
+pub struct MediaPosition {
+    pub current_time: f32,
+    pub duration: Option<f32>,
+    pub player_state: PlayerState,
+}
+
+impl MediaPosition {
+    pub fn progress(&self) -> Option<f32> {
+        self.duration
+            .filter(|duration| *duration > 0.)
+            .map(|duration| (self.current_time / duration).clamp(0., 1.))
+    }
+
+    pub fn remaining(&self) -> Option<f32> {
+        self.duration
+            .map(|duration| (duration - self.current_time).max(0.))
+    }
+}
+
+impl Connection {
+    pub async fn play_url<T: Into<String>, U: Into<String>>(
+        &self,
+        url: T,
+        content_type: U,
+    ) -> Result<(), Error> {
+        self.play(Media {
+            stream_type: StreamType::Buffered,
+            duration: None,
+            metadata: None,
+            content_type: content_type.into(),
+            content_id: url.into(),
+        })
+        .await
+    }
+
+    pub async fn play(&self, media: Media) -> Result<(), Error> {
+        self.enqueue(
+            vec![move || Ok(media.clone())],
+            false,
+            Priority::Normal,
+            Preemption::Immediate,
+            None,
+            &Ticket::default(),
+            None,
+        )
+        .await
+    }
+
+    pub async fn media_status(&self) -> Result<Option<MediaStatus>, Error> {
+        let session = match self.session() {
+            Some(session) => session,
+            None => return Ok(None),
+        };
+        let session = &session;
+        self.run(move |device| async move {
+            Ok(device
+                .media_status(&session.transport_id, session.media_session_id)
+                .await?
+                .into_iter()
+                .next())
+        })
+        .await
+    }
+
+    pub async fn media_position(&self) -> Result<Option<MediaPosition>, Error> {
+        Ok(self.media_status().await?.map(|status| MediaPosition {
+            current_time: status.current_time.unwrap_or(0.),
+            duration: status.duration,
+            player_state: status.player_state,
+        }))
+    }
+
+    pub async fn pause(&self) -> Result<MediaStatus, Error> {
+        let (transport_id, status) = self.active_media().await?;
+        if !status.commands.can_pause() {
+            return Err(Error::UnsupportedFeature("pause"));
+        }
+        self.control(&transport_id, status.media_session_id, "PAUSE")
+            .await
+    }
+
+    pub async fn resume(&self) -> Result<MediaStatus, Error> {
+        let (transport_id, status) = self.active_media().await?;
+        self.control(&transport_id, status.media_session_id, "PLAY")
+            .await
+    }
+
+    pub async fn stop(&self) -> Result<(), Error> {
+        let (transport_id, status) = self.active_media().await?;
+        let (transport_id, id) = (&transport_id, status.media_session_id);
+        self.run(move |device| async move {
+            device
+                .control(transport_id, id, json!({ "type": "STOP" }))
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn seek(&self, position: f32) -> Result<MediaStatus, Error> {
+        let (transport_id, status) = self.active_media().await?;
+        if !status.commands.can_seek() {
+            return Err(Error::UnsupportedFeature("seek"));
+        }
+        let (transport_id, id) = (&transport_id, status.media_session_id);
+        self.run(move |device| async move {
+            device
+                .control(
+                    transport_id,
+                    id,
+                    json!({ "type": "SEEK", "currentTime": position }),
+                )
+                .await?
+                .ok_or(Error::NoActiveMedia)
+        })
+        .await
+    }
+
+    pub(crate) async fn wait_idle(&self) -> Result<(), Error> {
+        loop {
+            match self.media_status().await? {
+                Some(status) if !matches!(status.player_state, PlayerState::Idle) => {}
+                _ => return Ok(()),
+            }
+            Timer::after(STATUS_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn control(
+        &self,
+        transport_id: &str,
+        media_session_id: i32,
+        kind: &'static str,
+    ) -> Result<MediaStatus, Error> {
+        self.run(move |device| async move {
+            device
+                .control(transport_id, media_session_id, json!({ "type": kind }))
+                .await?
+                .ok_or(Error::NoActiveMedia)
+        })
+        .await
+    }
+
+    async fn active_media(&self) -> Result<(String, MediaStatus), Error> {
+        let transport_id = self.session().ok_or(Error::NoActiveMedia)?.transport_id;
+        let status = self.media_status().await?.ok_or(Error::NoActiveMedia)?;
+        Ok((transport_id, status))
+    }
+}
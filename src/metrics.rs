@@ -0,0 +1,187 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use crate::{DeviceId, Error};
+
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10.];
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    counters: Mutex<BTreeMap<(&'static str, Labels), u64>>,
+    histograms: Mutex<BTreeMap<&'static str, Histogram>>,
+}
+
+impl Metrics {
+    pub fn global() -> &'static Metrics {
+        static METRICS: OnceLock<Metrics> = OnceLock::new();
+        METRICS.get_or_init(Metrics::default)
+    }
+
+    fn increment(&self, name: &'static str, labels: Labels) {
+        *self
+            .counters
+            .lock()
+            .unwrap()
+            .entry((name, labels))
+            .or_default() += 1;
+    }
+
+    fn observe(&self, name: &'static str, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let mut histograms = self.histograms.lock().unwrap();
+        let histogram = histograms.entry(name).or_default();
+        for (count, bound) in histogram.buckets.iter_mut().zip(&BUCKETS) {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut previous = None;
+        for ((name, labels), value) in self.counters.lock().unwrap().iter() {
+            if previous != Some(*name) {
+                let _ = writeln!(out, "# TYPE {} counter", name);
+                previous = Some(*name);
+            }
+            let _ = writeln!(out, "{}{} {}", name, format_labels(labels), value);
+        }
+        for (name, histogram) in self.histograms.lock().unwrap().iter() {
+            let _ = writeln!(out, "# TYPE {} histogram", name);
+            for (count, bound) in histogram.buckets.iter().zip(&BUCKETS) {
+                let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+            }
+            let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count);
+            let _ = writeln!(out, "{}_sum {}", name, histogram.sum);
+            let _ = writeln!(out, "{}_count {}", name, histogram.count);
+        }
+        out
+    }
+}
+
+pub(crate) fn announcement(device: &DeviceId, result: &Result<(), Error>) {
+    match result {
+        Ok(()) => Metrics::global().increment(
+            "cast_notify_announcements_total",
+            vec![("device", device.name.clone())],
+        ),
+        Err(e) => Metrics::global().increment(
+            "cast_notify_announcement_failures_total",
+            vec![("device", device.name.clone()), ("kind", kind(e))],
+        ),
+    }
+}
+
+pub(crate) fn tts_fetch(duration: Duration) {
+    Metrics::global().observe("cast_notify_tts_fetch_seconds", duration);
+}
+
+pub(crate) fn connected(duration: Duration) {
+    Metrics::global().observe("cast_notify_connect_seconds", duration);
+}
+
+pub(crate) fn discovered() {
+    Metrics::global().increment("cast_notify_devices_discovered_total", Vec::new());
+}
+
+fn kind(error: &Error) -> String {
+    let debug = format!("{:?}", error);
+    debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+fn format_labels(labels: &[(&'static str, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<_> = labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_groups_counters_by_name() {
+        let metrics = Metrics::default();
+        metrics.increment("announcements_total", vec![("device", "a".to_owned())]);
+        metrics.increment("announcements_total", vec![("device", "b".to_owned())]);
+        metrics.increment("announcements_total", vec![("device", "a".to_owned())]);
+        metrics.increment("discovered_total", Vec::new());
+        assert_eq!(
+            metrics.render(),
+            "# TYPE announcements_total counter\n\
+             announcements_total{device=\"a\"} 2\n\
+             announcements_total{device=\"b\"} 1\n\
+             # TYPE discovered_total counter\n\
+             discovered_total 1\n"
+        );
+    }
+
+    #[test]
+    fn render_escapes_label_values() {
+        let metrics = Metrics::default();
+        metrics.increment(
+            "announcements_total",
+            vec![("device", "Living \"Room\"\\\nTV".to_owned())],
+        );
+        assert!(metrics
+            .render()
+            .contains("announcements_total{device=\"Living \\\"Room\\\"\\\\\\nTV\"} 1\n"));
+    }
+
+    #[test]
+    fn render_emits_cumulative_histogram_buckets() {
+        let metrics = Metrics::default();
+        metrics.observe("fetch_seconds", Duration::from_millis(500));
+        metrics.observe("fetch_seconds", Duration::from_secs(2));
+        let rendered = metrics.render();
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines[0], "# TYPE fetch_seconds histogram");
+        assert_eq!(lines[1], "fetch_seconds_bucket{le=\"0.005\"} 0");
+        assert_eq!(lines[6], "fetch_seconds_bucket{le=\"0.25\"} 0");
+        assert_eq!(lines[7], "fetch_seconds_bucket{le=\"0.5\"} 1");
+        assert_eq!(lines[8], "fetch_seconds_bucket{le=\"1\"} 1");
+        assert_eq!(lines[9], "fetch_seconds_bucket{le=\"2.5\"} 2");
+        assert_eq!(lines[11], "fetch_seconds_bucket{le=\"10\"} 2");
+        assert_eq!(lines[12], "fetch_seconds_bucket{le=\"+Inf\"} 2");
+        assert_eq!(lines[13], "fetch_seconds_sum 2.5");
+        assert_eq!(lines[14], "fetch_seconds_count 2");
+        assert_eq!(lines.len(), 15);
+    }
+
+    #[test]
+    fn kind_is_the_variant_name() {
+        assert_eq!(kind(&Error::NoDevices), "NoDevices");
+        assert_eq!(kind(&Error::Rejected("LOAD_FAILED".to_owned())), "Rejected");
+    }
+}
@@ -0,0 +1,122 @@
+use std::convert::TryFrom;
+
+use serde_json::{json, Value};
+
+use crate::{Connection, Error, MediaCommands, NAMESPACE_MEDIA};
+
+/// Cast devices play one app at a time: loading an announcement launches the Default Media
+/// Receiver, which ends whatever was playing, so there is no way to mix it over other audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum MixMode {
+    /// Leave whatever was playing stopped.
+    #[default]
+    Replace,
+    /// Relaunch the interrupted app afterwards and reload its media where it left off, at the
+    /// stream volume it had.
+    Interrupt,
+}
+
+pub(crate) struct Playback {
+    app_id: String,
+    media: Option<(Value, f64)>,
+    stream_volume: Option<f64>,
+}
+
+impl Connection {
+    pub(crate) async fn playback(&self) -> Result<Option<Playback>, Error> {
+        let ours = self.session().map(|session| session.session_id);
+        let ours = &ours;
+        self.run(move |device| async move {
+            let app = match device
+                .receiver_status()
+                .await?
+                .applications
+                .into_iter()
+                .next()
+            {
+                Some(app) if Some(&app.session_id) != ours.as_ref() => app,
+                _ => return Ok(None),
+            };
+            if !app.supports_media {
+                return Ok(Some(Playback {
+                    app_id: app.app_id,
+                    media: None,
+                    stream_volume: None,
+                }));
+            }
+            device.connect(&app.transport_id).await?;
+            let status = device
+                .request(
+                    &app.transport_id,
+                    NAMESPACE_MEDIA,
+                    json!({ "type": "GET_STATUS" }),
+                )
+                .await?;
+            let entry = &status["status"][0];
+            let media = Some((
+                entry["media"].clone(),
+                entry["currentTime"].as_f64().unwrap_or(0.),
+            ))
+            .filter(|(media, _)| media.is_object());
+            let commands = entry["supportedMediaCommands"]
+                .as_u64()
+                .and_then(|bits| u32::try_from(bits).ok())
+                .map(MediaCommands::from_bits)
+                .unwrap_or_default();
+            let stream_volume = Some(entry["volume"]["level"].as_f64().unwrap_or(1.))
+                .filter(|_| commands.can_set_stream_volume());
+            Ok(Some(Playback {
+                app_id: app.app_id,
+                media,
+                stream_volume,
+            }))
+        })
+        .await
+    }
+
+    pub(crate) async fn resume_playback(&self, playback: Playback) -> Result<(), Error> {
+        let session = self.session.lock().unwrap().take();
+        let (session, playback) = (&session, &playback);
+        self.run_once(
+            "launch",
+            self.settings.timeouts.command,
+            move |device| async move {
+                if let Some(session) = session.as_ref().filter(|session| session.launched) {
+                    device.stop_app(&session.session_id).await?;
+                }
+                let app = device.launch(&playback.app_id).await?;
+                if let Some((media, position)) = &playback.media {
+                    device.connect(&app.transport_id).await?;
+                    let status = device
+                        .request(
+                            &app.transport_id,
+                            NAMESPACE_MEDIA,
+                            json!({
+                                "type": "LOAD",
+                                "sessionId": app.session_id,
+                                "autoplay": true,
+                                "currentTime": position,
+                                "media": media,
+                            }),
+                        )
+                        .await?;
+                    let resumed = status["status"][0]["mediaSessionId"]
+                        .as_i64()
+                        .and_then(|id| i32::try_from(id).ok());
+                    if let (Some(level), Some(id)) = (playback.stream_volume, resumed) {
+                        device
+                            .control(
+                                &app.transport_id,
+                                id,
+                                json!({ "type": "SET_VOLUME", "volume": { "level": level } }),
+                            )
+                            .await?;
+                    }
+                }
+                Ok(())
+            },
+        )
+        .await
+    }
+}
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, thread, time::Duration};
+
+use futures::{channel::mpsc, future::join_all, StreamExt};
+use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, Publish, QoS};
+use serde_json::json;
+
+use crate::{request, runtime::unblock, Connection, Error};
+
+const DEFAULT_PREFIX: &str = "cast-notify";
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+pub struct Bridge {
+    options: MqttOptions,
+    prefix: String,
+    devices: HashMap<String, Connection>,
+}
+
+impl Bridge {
+    pub fn new<T: Into<String>, U: Into<String>>(client_id: T, host: U, port: u16) -> Self {
+        Bridge {
+            options: MqttOptions::new(client_id, host, port),
+            prefix: DEFAULT_PREFIX.to_owned(),
+            devices: HashMap::new(),
+        }
+    }
+
+    pub fn credentials<T: Into<String>, U: Into<String>>(
+        mut self,
+        username: T,
+        password: U,
+    ) -> Self {
+        self.options.set_credentials(username, password);
+        self
+    }
+
+    pub fn prefix<T: Into<String>>(mut self, prefix: T) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn device<T: Into<String>>(mut self, name: T, connection: Connection) -> Self {
+        self.devices.insert(name.into(), connection);
+        self
+    }
+
+    pub async fn run(mut self) -> Result<(), Error> {
+        let availability = format!("{}/availability", self.prefix);
+        self.options.set_last_will(LastWill::new(
+            &availability,
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        let (client, mut connection) = Client::new(self.options.clone(), 16);
+        let (sender, events) = mpsc::unbounded();
+        thread::spawn(move || {
+            for event in connection.iter() {
+                let failed = event.is_err();
+                if sender.unbounded_send(event).is_err() {
+                    return;
+                }
+                if failed {
+                    thread::sleep(RECONNECT_DELAY);
+                }
+            }
+        });
+        let (bridge, client, availability) = (&self, &client, availability.as_str());
+        events
+            .for_each_concurrent(None, |event| async move {
+                let result = match event {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        bridge.announce(client, availability).await
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        bridge.handle(client, publish).await
+                    }
+                    Err(_e) => {
+                        log!(warn, error = %_e, "mqtt connection error");
+                        Ok(())
+                    }
+                    _ => Ok(()),
+                };
+                if let Err(_e) = result {
+                    log!(warn, error = %_e, "failed to handle mqtt event");
+                }
+            })
+            .await;
+        Ok(())
+    }
+
+    async fn announce(&self, client: &Client, availability: &str) -> Result<(), Error> {
+        let topic = format!("{}/+/say", self.prefix);
+        let subscriber = client.clone();
+        unblock(move || subscriber.subscribe(topic, QoS::AtLeastOnce)).await?;
+        publish(client, availability.to_owned(), "online".into()).await?;
+        join_all(self.devices.iter().map(|(name, connection)| async move {
+            if let Err(_e) = self.report(client, name, connection).await {
+                log!(warn, device = %name, error = %_e, "failed to report mqtt device status");
+            }
+        }))
+        .await;
+        Ok(())
+    }
+
+    async fn handle(&self, client: &Client, publish: Publish) -> Result<(), Error> {
+        let name = match publish
+            .topic
+            .strip_prefix(&self.prefix)
+            .and_then(|topic| topic.strip_prefix('/'))
+            .and_then(|topic| topic.strip_suffix("/say"))
+        {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let connection = match self.devices.get(name) {
+            Some(connection) => connection,
+            None => return Ok(()),
+        };
+        let (message, options) = request::parse(&publish.payload);
+        if let Err(e) = connection.say_with_options(message, options).await {
+            log!(warn, device = %name, error = %e, "mqtt announcement failed");
+            let result = json!({ "error": e.to_string() });
+            publish(
+                client,
+                format!("{}/{}/result", self.prefix, name),
+                result.to_string(),
+            )
+            .await?;
+        }
+        self.report(client, name, connection).await
+    }
+
+    async fn report(
+        &self,
+        client: &Client,
+        name: &str,
+        connection: &Connection,
+    ) -> Result<(), Error> {
+        let topic = format!("{}/{}", self.prefix, name);
+        let status = match connection.status().await {
+            Ok(status) => status,
+            Err(_) => {
+                return publish(client, format!("{}/availability", topic), "offline".into()).await
+            }
+        };
+        publish(client, format!("{}/availability", topic), "online".into()).await?;
+        let status = request::summary(status).to_string();
+        publish(client, format!("{}/status", topic), status).await
+    }
+}
+
+async fn publish(client: &Client, topic: String, payload: String) -> Result<(), Error> {
+    let client = client.clone();
+    unblock(move || client.publish(topic, QoS::AtLeastOnce, true, payload)).await?;
+    Ok(())
+}
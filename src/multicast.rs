@@ -0,0 +1,168 @@
+use std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6, UdpSocket},
+    time::Instant,
+};
+
+use async_io::{Async, Timer};
+use dns_parser::{Builder, Packet, QueryClass, QueryType};
+use futures::{
+    future::{ready, select, Either},
+    pin_mut,
+    stream::{self, once, select_all},
+    Stream,
+};
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+
+use crate::{Error, QUERY_INTERVAL, SERVICE_NAME};
+
+const MDNS_V4: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_V6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb);
+const MDNS_PORT: u16 = 5353;
+const MAX_PACKET_SIZE: usize = 9000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketOptions {
+    pub interface_addr: Option<Ipv4Addr>,
+    pub interface_index: Option<u32>,
+    pub multicast_loop: bool,
+    pub hop_limit: u32,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        SocketOptions {
+            interface_addr: None,
+            interface_index: None,
+            multicast_loop: true,
+            hop_limit: 255,
+        }
+    }
+}
+
+impl SocketOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn interface_addr(mut self, addr: Ipv4Addr) -> Self {
+        self.interface_addr = Some(addr);
+        self
+    }
+
+    pub fn interface_index(mut self, index: u32) -> Self {
+        self.interface_index = Some(index);
+        self
+    }
+
+    pub fn multicast_loop(mut self, enabled: bool) -> Self {
+        self.multicast_loop = enabled;
+        self
+    }
+
+    pub fn hop_limit(mut self, hops: u32) -> Self {
+        self.hop_limit = hops;
+        self
+    }
+}
+
+pub(crate) fn responses(
+    interfaces: &[Ipv4Addr],
+    options: SocketOptions,
+) -> impl Stream<Item = Result<mdns::Response, Error>> {
+    let mut sockets: Vec<_> = if interfaces.is_empty() {
+        let interface = options.interface_addr.unwrap_or(Ipv4Addr::UNSPECIFIED);
+        vec![bind_v4(interface, options)]
+    } else {
+        interfaces
+            .iter()
+            .map(|&interface| bind_v4(interface, options))
+            .collect()
+    };
+    if let Some(index) = options.interface_index {
+        sockets.push(bind_v6(index, options));
+    }
+    select_all(sockets.into_iter().map(|socket| {
+        Box::pin(match socket {
+            Ok((socket, destination)) => Either::Left(listen(socket, destination)),
+            Err(e) => Either::Right(once(ready(Err(e.into())))),
+        })
+    }))
+}
+
+fn bind_v4(
+    interface: Ipv4Addr,
+    options: SocketOptions,
+) -> io::Result<(Async<UdpSocket>, SocketAddr)> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_multicast_if_v4(&interface)?;
+    socket.set_multicast_loop_v4(options.multicast_loop)?;
+    socket.set_multicast_ttl_v4(options.hop_limit)?;
+    socket.bind(&SockAddr::from(SocketAddr::new(interface.into(), 0)))?;
+    let destination = SocketAddr::new(MDNS_V4.into(), MDNS_PORT);
+    Ok((Async::new(UdpSocket::from(socket))?, destination))
+}
+
+fn bind_v6(index: u32, options: SocketOptions) -> io::Result<(Async<UdpSocket>, SocketAddr)> {
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_only_v6(true)?;
+    socket.set_multicast_if_v6(index)?;
+    socket.set_multicast_loop_v6(options.multicast_loop)?;
+    socket.set_multicast_hops_v6(options.hop_limit)?;
+    socket.bind(&SockAddr::from(SocketAddr::new(
+        Ipv6Addr::UNSPECIFIED.into(),
+        0,
+    )))?;
+    let destination = SocketAddrV6::new(MDNS_V6, MDNS_PORT, 0, index).into();
+    Ok((Async::new(UdpSocket::from(socket))?, destination))
+}
+
+fn query_packet() -> Vec<u8> {
+    let mut builder = Builder::new_query(0, false);
+    builder.add_question(SERVICE_NAME, true, QueryType::PTR, QueryClass::IN);
+    builder.build().unwrap_or_else(|truncated| truncated)
+}
+
+fn listen(
+    socket: Async<UdpSocket>,
+    destination: SocketAddr,
+) -> impl Stream<Item = Result<mdns::Response, Error>> {
+    let query = query_packet();
+    stream::unfold(
+        (socket, query, Instant::now()),
+        move |(socket, query, mut next)| async move {
+            let mut buffer = vec![0; MAX_PACKET_SIZE];
+            loop {
+                if Instant::now() >= next {
+                    next = Instant::now() + QUERY_INTERVAL;
+                    if let Err(e) = socket.send_to(&query, destination).await {
+                        return Some((Err(e.into()), (socket, query, next)));
+                    }
+                }
+                let received = {
+                    let receive = socket.recv_from(&mut buffer);
+                    pin_mut!(receive);
+                    match select(receive, Timer::at(next)).await {
+                        Either::Left((received, _)) => received,
+                        Either::Right(_) => continue,
+                    }
+                };
+                let len = match received {
+                    Ok((len, _)) => len,
+                    Err(e) => return Some((Err(e.into()), (socket, query, next))),
+                };
+                let response = match Packet::parse(&buffer[..len]) {
+                    Ok(packet) if !packet.header.query => mdns::Response::from_packet(&packet),
+                    _ => continue,
+                };
+                if response
+                    .answers
+                    .iter()
+                    .any(|record| record.name == SERVICE_NAME)
+                {
+                    return Some((Ok(response), (socket, query, next)));
+                }
+            }
+        },
+    )
+}
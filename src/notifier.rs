@@ -0,0 +1,314 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures::{stream, StreamExt};
+
+use crate::{
+    BroadcastOutcome, ChimeSource, Connection, Error, History, HistoryEntry, HistoryOutcome,
+    Outcome, Pool, SayOptions, Theme, TtsCache, TtsProvider, Zones,
+};
+
+const ZONE_RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+const ZONE_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    text: String,
+    options: SayOptions,
+}
+
+impl Template {
+    pub fn lang<T: Into<String>>(&mut self, language: T) -> &mut Self {
+        self.options.lang = Some(language.into());
+        self
+    }
+
+    pub fn volume(&mut self, level: f32) -> &mut Self {
+        self.options.volume = Some(level.clamp(0., 1.));
+        self
+    }
+
+    pub fn voice<T: Into<String>>(&mut self, voice: T) -> &mut Self {
+        self.options.voice = Some(voice.into());
+        self
+    }
+
+    pub fn chime(&mut self, source: ChimeSource) -> &mut Self {
+        self.options.chime = Some(source);
+        self
+    }
+
+    pub fn theme(&mut self, theme: &Theme) -> &mut Self {
+        theme.apply(&mut self.options);
+        self
+    }
+
+    pub fn render<I, K, V>(&self, vars: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let vars: HashMap<String, String> = vars
+            .into_iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+            .collect();
+        let mut out = String::with_capacity(self.text.len());
+        let mut rest = self.text.as_str();
+        while let Some(open) = rest.find(['{', '}']) {
+            out.push_str(&rest[..open]);
+            let brace = &rest[open..open + 1];
+            rest = &rest[open + 1..];
+            if rest.starts_with(brace) {
+                out.push_str(brace);
+                rest = &rest[1..];
+                continue;
+            }
+            if brace == "}" {
+                return Err(Error::InvalidTemplate(self.text.clone()));
+            }
+            let close = rest
+                .find('}')
+                .ok_or_else(|| Error::InvalidTemplate(self.text.clone()))?;
+            let name = rest[..close].trim();
+            let value = vars
+                .get(name)
+                .ok_or_else(|| Error::MissingVariable(name.to_string()))?;
+            out.push_str(value);
+            rest = &rest[close + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
+
+pub struct Notifier {
+    connection: Connection,
+    templates: HashMap<String, Template>,
+    history: Option<Arc<History>>,
+    zones: Zones,
+    pool: Arc<Pool>,
+    cache: Option<Arc<TtsCache<Arc<dyn TtsProvider>>>>,
+}
+
+impl Notifier {
+    pub fn new(connection: Connection) -> Self {
+        Notifier {
+            connection,
+            templates: HashMap::new(),
+            history: None,
+            zones: Zones::default(),
+            pool: Arc::new(Pool::new()),
+            cache: None,
+        }
+    }
+
+    pub fn with_history(mut self, history: Arc<History>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_deref()
+    }
+
+    pub fn with_zones(mut self, zones: Zones) -> Self {
+        self.zones = zones;
+        self
+    }
+
+    pub fn zones(&self) -> &Zones {
+        &self.zones
+    }
+
+    /// Zone members are connected through this pool, so repeated zone announcements reuse
+    /// their connections and `health` can report on them.
+    pub fn with_pool(mut self, pool: Arc<Pool>) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    pub fn template<T: Into<String>, U: Into<String>>(
+        &mut self,
+        name: T,
+        text: U,
+    ) -> &mut Template {
+        let template = Template {
+            text: text.into(),
+            options: SayOptions::default(),
+        };
+        let name = name.into();
+        self.templates.insert(name.clone(), template);
+        self.templates.get_mut(&name).unwrap()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Template> {
+        self.templates.get(name)
+    }
+
+    pub async fn preload<I, T>(&mut self, phrases: I, language: &str) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let options = SayOptions {
+            lang: Some(language.to_string()),
+            ..SayOptions::default()
+        };
+        self.preload_with_options(phrases, &options).await
+    }
+
+    pub async fn preload_with_options<I, T>(
+        &mut self,
+        phrases: I,
+        options: &SayOptions,
+    ) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let cache = match &self.cache {
+            Some(cache) => cache.clone(),
+            None => {
+                let server = self.connection.audio_server()?;
+                let cache = Arc::new(TtsCache::new(self.connection.provider.clone(), server));
+                self.cache = Some(cache.clone());
+                cache
+            }
+        };
+        for phrase in phrases {
+            self.connection
+                .prefetch(phrase.into(), options, cache.clone())
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn provider(&self) -> Arc<dyn TtsProvider> {
+        match &self.cache {
+            Some(cache) => cache.clone(),
+            None => self.connection.provider.clone(),
+        }
+    }
+
+    pub async fn notify<I, K, V>(&self, name: &str, vars: I) -> Result<Outcome, Error>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let template = self
+            .templates
+            .get(name)
+            .ok_or_else(|| Error::UnknownTemplate(name.to_string()))?;
+        let message = template.render(vars)?;
+        let options = template.options.clone();
+        let result = match self
+            .connection
+            .announce(message.as_str(), options, self.provider())
+            .await
+        {
+            Ok(handle) => handle.done().await,
+            Err(e) => Err(e),
+        };
+        if let Some(history) = &self.history {
+            let outcome = HistoryOutcome::of(&result);
+            let entry = HistoryEntry::new(self.connection.device_id(), message, outcome);
+            if let Err(_e) = history.record(entry).await {
+                log!(warn, error = %_e, "failed to record announcement history");
+            }
+        }
+        result
+    }
+
+    pub async fn say_to_zone(&self, label: &str, message: &str) -> Result<BroadcastOutcome, Error> {
+        let (targets, errors) = self.zones.resolve(label, ZONE_RESOLVE_TIMEOUT).await?;
+        let results = stream::iter(&targets)
+            .map(|target| async move {
+                let connection = self.pool.get(target).await?;
+                let result = connection.say(message).await.map(drop);
+                if result.is_err() && !connection.health().connected {
+                    self.pool.evict(target);
+                }
+                Ok::<_, Error>((connection, result))
+            })
+            .buffer_unordered(ZONE_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+        let mut outcome = BroadcastOutcome::new();
+        for result in results {
+            match result {
+                Ok((connection, result)) => outcome.record(&connection, result),
+                Err(e) => outcome.fail(e),
+            }
+        }
+        for error in errors {
+            outcome.fail(error);
+        }
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(text: &str, vars: &[(&str, &str)]) -> Result<String, Error> {
+        let template = Template {
+            text: text.to_owned(),
+            options: SayOptions::default(),
+        };
+        template.render(vars.iter().copied())
+    }
+
+    #[test]
+    fn substitutes_variables_and_trims_their_names() {
+        let vars = [("door", "front"), ("minutes", "5")];
+        assert_eq!(
+            render(
+                "The {door} door has been open for { minutes } minutes",
+                &vars
+            )
+            .unwrap(),
+            "The front door has been open for 5 minutes"
+        );
+        assert_eq!(render("{door}{door}", &vars).unwrap(), "frontfront");
+        assert_eq!(render("No placeholders", &[]).unwrap(), "No placeholders");
+    }
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        let vars = [("name", "x")];
+        assert_eq!(render("{{name}} is {name}", &vars).unwrap(), "{name} is x");
+        assert_eq!(render("}}{{", &[]).unwrap(), "}{");
+    }
+
+    #[test]
+    fn does_not_substitute_inside_values() {
+        let vars = [("a", "{b}"), ("b", "oops")];
+        assert_eq!(render("{a}", &vars).unwrap(), "{b}");
+    }
+
+    #[test]
+    fn rejects_unbalanced_braces_and_missing_variables() {
+        assert!(matches!(
+            render("Hello {name", &[]),
+            Err(Error::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            render("Hello name}", &[]),
+            Err(Error::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            render("Hello {name}", &[("other", "x")]),
+            Err(Error::MissingVariable(name)) if name == "name"
+        ));
+    }
+}
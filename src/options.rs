@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use async_native_tls::{Certificate, TlsConnector};
+
+use crate::{Error, RetryPolicy, DEFAULT_DESTINATION_ID};
+
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    pub(crate) verify_tls: bool,
+    pub(crate) root_certificate: Option<Vec<u8>>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) destination_id: String,
+    pub(crate) heartbeat: Option<Duration>,
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        ConnectOptions {
+            verify_tls: false,
+            root_certificate: None,
+            timeout: None,
+            retry: RetryPolicy::default(),
+            destination_id: DEFAULT_DESTINATION_ID.into(),
+            heartbeat: None,
+        }
+    }
+}
+
+impl ConnectOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn verify_tls(mut self, verify: bool) -> Self {
+        self.verify_tls = verify;
+        self
+    }
+
+    pub fn root_certificate<T: Into<Vec<u8>>>(mut self, pem: T) -> Self {
+        self.root_certificate = Some(pem.into());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retry.attempts = retries;
+        self
+    }
+
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    pub fn destination_id<T: Into<String>>(mut self, destination_id: T) -> Self {
+        self.destination_id = destination_id.into();
+        self
+    }
+
+    pub fn heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = Some(interval);
+        self
+    }
+
+    /// Cast devices present certificates chained to a private root and never for a hostname,
+    /// so verification skips the hostname check and anchors the chain at `root_certificate`.
+    pub(crate) fn tls(&self) -> Result<TlsConnector, Error> {
+        let connector = TlsConnector::new().danger_accept_invalid_hostnames(true);
+        if !self.verify_tls {
+            return Ok(connector.danger_accept_invalid_certs(true));
+        }
+        let pem = self
+            .root_certificate
+            .as_ref()
+            .ok_or(Error::MissingRootCertificate)?;
+        Ok(connector.add_root_certificate(Certificate::from_pem(pem)?))
+    }
+}
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use async_io::Timer;
+use futures::{future::join_all, StreamExt};
+
+use crate::{DiscoverOptions, Error, Target};
+
+const DEFAULT_WINDOW: Duration = Duration::from_secs(3);
+const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub struct Criteria {
+    options: DiscoverOptions,
+    window: Duration,
+    room: Option<String>,
+    groups: bool,
+    latency: Option<Duration>,
+}
+
+impl Default for Criteria {
+    fn default() -> Self {
+        Criteria {
+            options: DiscoverOptions::default(),
+            window: DEFAULT_WINDOW,
+            room: None,
+            groups: false,
+            latency: None,
+        }
+    }
+}
+
+impl Criteria {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn options(mut self, options: DiscoverOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn room<T: Into<String>>(mut self, label: T) -> Self {
+        self.room = Some(label.into().to_lowercase());
+        self
+    }
+
+    pub fn prefer_groups(mut self) -> Self {
+        self.groups = true;
+        self
+    }
+
+    pub fn prefer_low_latency(mut self) -> Self {
+        self.latency = Some(DEFAULT_PROBE_TIMEOUT);
+        self
+    }
+
+    pub fn probe_timeout(mut self, timeout: Duration) -> Self {
+        self.latency = Some(timeout);
+        self
+    }
+
+    pub async fn pick<I: IntoIterator<Item = Target>>(&self, targets: I) -> Result<Target, Error> {
+        let scored: Vec<(u32, Target)> = targets
+            .into_iter()
+            .map(|target| (self.score(&target), target))
+            .collect();
+        let best = scored
+            .iter()
+            .map(|(score, _)| *score)
+            .max()
+            .ok_or(Error::NoDevices)?;
+        let mut candidates: Vec<Target> = scored
+            .into_iter()
+            .filter(|(score, _)| *score == best)
+            .map(|(_, target)| target)
+            .collect();
+        let timeout = match self.latency {
+            Some(timeout) if candidates.len() > 1 => timeout,
+            _ => return Ok(candidates.swap_remove(0)),
+        };
+        let probes = join_all(candidates.iter().map(|target| target.probe(timeout))).await;
+        let fastest = probes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, probe)| Some((i, probe.as_ref().ok()?.connect_latency)))
+            .min_by_key(|(_, latency)| *latency)
+            .map_or(0, |(i, _)| i);
+        Ok(candidates.swap_remove(fastest))
+    }
+
+    fn score(&self, target: &Target) -> u32 {
+        let mut score = 0;
+        if let Some(room) = &self.room {
+            if target.name().to_lowercase().contains(room.as_str()) {
+                score += 2;
+            }
+        }
+        if self.groups && target.is_group() {
+            score += 1;
+        }
+        score
+    }
+}
+
+pub async fn pick_best(criteria: Criteria) -> Result<Target, Error> {
+    let targets: Vec<Target> = criteria
+        .options
+        .clone()
+        .discover_resilient(|_e| {
+            log!(warn, error = %_e, "discarding malformed discovery response");
+        })
+        .take_until(Timer::after(criteria.window))
+        .collect()
+        .await;
+    criteria.pick(targets).await
+}
@@ -0,0 +1,365 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_io::Timer;
+use chrono::{Local, NaiveTime};
+
+use crate::{Connection, Error, PlayerState};
+
+const CASTING_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(rename_all = "lowercase"))]
+pub enum QuietAction {
+    Suppress,
+    Defer,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub action: QuietAction,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    fn remaining(&self, time: NaiveTime) -> Duration {
+        let remaining = (self.end - time).num_seconds().rem_euclid(24 * 60 * 60);
+        Duration::from_secs(remaining as u64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct RateLimit {
+    pub count: usize,
+    pub per: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressReason {
+    QuietHours,
+    RateLimited,
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Policy {
+    pub quiet_hours: Option<QuietHours>,
+    pub min_volume: Option<f32>,
+    pub defer_if_casting: bool,
+    pub rate_limit: Option<RateLimit>,
+    pub dedup_window: Option<Duration>,
+}
+
+#[derive(Default)]
+pub(crate) struct Recent {
+    spoken: VecDeque<(Instant, String)>,
+}
+
+pub(crate) struct Admission<'a> {
+    recent: &'a Mutex<Recent>,
+    entry: Option<(Instant, String)>,
+}
+
+impl Admission<'_> {
+    pub(crate) fn settle<T>(mut self, result: &Result<T, Error>) {
+        if result.is_ok() {
+            self.entry = None;
+        }
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            self.recent.lock().unwrap().retract(&entry);
+        }
+    }
+}
+
+impl Recent {
+    fn admit(&mut self, message: &str, policy: &Policy) -> Result<Option<Instant>, SuppressReason> {
+        let now = Instant::now();
+        let horizon = policy
+            .rate_limit
+            .map(|limit| limit.per)
+            .max(policy.dedup_window)
+            .unwrap_or_default();
+        while let Some((at, _)) = self.spoken.front() {
+            if now.duration_since(*at) < horizon {
+                break;
+            }
+            self.spoken.pop_front();
+        }
+        if let Some(window) = policy.dedup_window {
+            if self
+                .spoken
+                .iter()
+                .any(|(at, spoken)| spoken == message && now.duration_since(*at) < window)
+            {
+                return Err(SuppressReason::Duplicate);
+            }
+        }
+        if let Some(limit) = policy.rate_limit {
+            let recent = self
+                .spoken
+                .iter()
+                .filter(|(at, _)| now.duration_since(*at) < limit.per)
+                .count();
+            if recent >= limit.count {
+                return Err(SuppressReason::RateLimited);
+            }
+        }
+        if horizon == Duration::ZERO {
+            return Ok(None);
+        }
+        self.spoken.push_back((now, message.to_owned()));
+        Ok(Some(now))
+    }
+
+    fn retract(&mut self, entry: &(Instant, String)) {
+        if let Some(index) = self.spoken.iter().position(|spoken| spoken == entry) {
+            self.spoken.remove(index);
+        }
+    }
+}
+
+impl Connection {
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    pub fn on_suppressed<F: Fn(&str, SuppressReason) + Send + Sync + 'static>(
+        &mut self,
+        callback: F,
+    ) {
+        self.on_suppressed = Some(Arc::new(callback));
+    }
+
+    pub(crate) async fn apply_policy(&self, message: &str) -> Result<Admission<'_>, Error> {
+        if let Some(quiet) = self.policy.quiet_hours {
+            let now = Local::now().time();
+            if quiet.contains(now) {
+                match quiet.action {
+                    QuietAction::Suppress => {
+                        return Err(self.suppress(message, SuppressReason::QuietHours))
+                    }
+                    QuietAction::Defer => {
+                        Timer::after(quiet.remaining(now)).await;
+                    }
+                }
+            }
+        }
+        if self.policy.defer_if_casting {
+            while self.is_casting().await? {
+                Timer::after(CASTING_POLL_INTERVAL).await;
+            }
+        }
+        let admitted = self.recent.lock().unwrap().admit(message, &self.policy);
+        let at = admitted.map_err(|reason| self.suppress(message, reason))?;
+        Ok(Admission {
+            recent: &self.recent,
+            entry: at.map(|at| (at, message.to_owned())),
+        })
+    }
+
+    fn suppress(&self, message: &str, reason: SuppressReason) -> Error {
+        log!(info, device = %self.link.id, ?reason, "announcement suppressed");
+        if let Some(callback) = &self.on_suppressed {
+            callback(message, reason);
+        }
+        Error::Suppressed(reason)
+    }
+
+    async fn is_casting(&self) -> Result<bool, Error> {
+        let status = self.status().await?;
+        let ours = self.session().map(|session| session.session_id);
+        let foreign = status
+            .app
+            .is_some_and(|app| Some(&app.session_id) != ours.as_ref());
+        let playing = status.media.is_some_and(|media| {
+            matches!(
+                media.player_state,
+                PlayerState::Playing | PlayerState::Buffering
+            )
+        });
+        Ok(foreign && playing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(50);
+
+    fn dedup() -> Policy {
+        Policy {
+            dedup_window: Some(WINDOW),
+            ..Policy::default()
+        }
+    }
+
+    fn admit<'a>(
+        recent: &'a Mutex<Recent>,
+        message: &str,
+        policy: &Policy,
+    ) -> Result<Admission<'a>, SuppressReason> {
+        let at = recent.lock().unwrap().admit(message, policy)?;
+        Ok(Admission {
+            recent,
+            entry: at.map(|at| (at, message.to_owned())),
+        })
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn quiet(start: NaiveTime, end: NaiveTime) -> QuietHours {
+        QuietHours {
+            start,
+            end,
+            action: QuietAction::Suppress,
+        }
+    }
+
+    #[test]
+    fn daytime_window_includes_start_and_excludes_end() {
+        let hours = quiet(at(9, 0), at(17, 0));
+        assert!(hours.contains(at(9, 0)));
+        assert!(hours.contains(at(16, 59)));
+        assert!(!hours.contains(at(17, 0)));
+        assert!(!hours.contains(at(8, 59)));
+        assert!(!hours.contains(at(23, 0)));
+    }
+
+    #[test]
+    fn overnight_window_wraps_past_midnight() {
+        let hours = quiet(at(22, 0), at(7, 0));
+        for time in [at(22, 0), at(23, 59), at(0, 0), at(3, 30), at(6, 59)] {
+            assert!(hours.contains(time), "{}", time);
+        }
+        for time in [at(7, 0), at(12, 0), at(21, 59)] {
+            assert!(!hours.contains(time), "{}", time);
+        }
+    }
+
+    #[test]
+    fn empty_window_contains_nothing() {
+        let hours = quiet(at(8, 0), at(8, 0));
+        assert!(!hours.contains(at(8, 0)));
+        assert!(!hours.contains(at(20, 0)));
+    }
+
+    #[test]
+    fn remaining_counts_to_the_end_of_the_window() {
+        let hours = quiet(at(9, 0), at(17, 0));
+        assert_eq!(hours.remaining(at(16, 0)), Duration::from_secs(60 * 60));
+        assert_eq!(hours.remaining(at(9, 0)), Duration::from_secs(8 * 60 * 60));
+    }
+
+    #[test]
+    fn remaining_wraps_past_midnight() {
+        let hours = quiet(at(22, 0), at(7, 0));
+        assert_eq!(hours.remaining(at(22, 0)), Duration::from_secs(9 * 60 * 60));
+        assert_eq!(hours.remaining(at(23, 0)), Duration::from_secs(8 * 60 * 60));
+        assert_eq!(hours.remaining(at(6, 30)), Duration::from_secs(30 * 60));
+    }
+
+    #[test]
+    fn duplicate_inside_the_window_is_suppressed() {
+        let recent = Mutex::new(Recent::default());
+        admit(&recent, "Door open", &dedup())
+            .unwrap()
+            .settle(&Ok(()));
+        assert!(matches!(
+            admit(&recent, "Door open", &dedup()),
+            Err(SuppressReason::Duplicate)
+        ));
+        assert!(admit(&recent, "Door closed", &dedup()).is_ok());
+    }
+
+    #[test]
+    fn duplicate_outside_the_window_is_admitted() {
+        let recent = Mutex::new(Recent::default());
+        admit(&recent, "Door open", &dedup())
+            .unwrap()
+            .settle(&Ok(()));
+        std::thread::sleep(WINDOW * 2);
+        admit(&recent, "Door open", &dedup())
+            .unwrap()
+            .settle(&Ok(()));
+        assert_eq!(recent.lock().unwrap().spoken.len(), 1);
+    }
+
+    #[test]
+    fn rate_limit_counts_only_announcements_inside_the_period() {
+        let policy = Policy {
+            rate_limit: Some(RateLimit {
+                count: 2,
+                per: WINDOW,
+            }),
+            ..Policy::default()
+        };
+        let recent = Mutex::new(Recent::default());
+        for message in ["one", "two"] {
+            admit(&recent, message, &policy).unwrap().settle(&Ok(()));
+        }
+        assert!(matches!(
+            admit(&recent, "three", &policy),
+            Err(SuppressReason::RateLimited)
+        ));
+        std::thread::sleep(WINDOW * 2);
+        assert!(admit(&recent, "three", &policy).is_ok());
+    }
+
+    #[test]
+    fn nothing_is_remembered_without_a_window() {
+        let recent = Mutex::new(Recent::default());
+        let admission = admit(&recent, "Door open", &Policy::default()).unwrap();
+        assert!(admission.entry.is_none());
+        admission.settle(&Ok(()));
+        assert!(recent.lock().unwrap().spoken.is_empty());
+    }
+
+    #[test]
+    fn failed_delivery_rolls_back_the_admission() {
+        let recent = Mutex::new(Recent::default());
+        admit(&recent, "Door open", &dedup())
+            .unwrap()
+            .settle(&Err::<(), _>(Error::Cancelled));
+        assert!(recent.lock().unwrap().spoken.is_empty());
+        assert!(admit(&recent, "Door open", &dedup()).is_ok());
+    }
+
+    #[test]
+    fn dropped_admission_rolls_back_only_its_own_entry() {
+        let recent = Mutex::new(Recent::default());
+        admit(&recent, "first", &dedup()).unwrap().settle(&Ok(()));
+        drop(admit(&recent, "second", &dedup()).unwrap());
+        let spoken = recent.into_inner().unwrap().spoken;
+        assert_eq!(spoken.len(), 1);
+        assert_eq!(spoken[0].1, "first");
+    }
+}
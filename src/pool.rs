@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{ConnectOptions, Connection, Error, Target, TargetKey};
+
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+struct Entry {
+    connection: Arc<Connection>,
+    last_used: Instant,
+    last_checked: Instant,
+}
+
+pub struct Pool {
+    options: ConnectOptions,
+    idle_timeout: Duration,
+    health_check_interval: Duration,
+    entries: Mutex<HashMap<TargetKey, Entry>>,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pool {
+    pub fn new() -> Self {
+        Self::with_options(ConnectOptions::default())
+    }
+
+    pub fn with_options(options: ConnectOptions) -> Self {
+        Pool {
+            options,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            health_check_interval: DEFAULT_HEALTH_CHECK_INTERVAL,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    pub fn health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
+    pub async fn get(&self, target: &Target) -> Result<Arc<Connection>, Error> {
+        self.prune();
+        let key = target.key();
+        let now = Instant::now();
+        let pooled = self.entries.lock().unwrap().get_mut(&key).map(|entry| {
+            entry.last_used = now;
+            let stale = now.duration_since(entry.last_checked) >= self.health_check_interval;
+            (entry.connection.clone(), stale)
+        });
+        match pooled {
+            Some((connection, false)) => return Ok(connection),
+            Some((connection, true)) => {
+                if connection.link.heartbeat().await.is_ok() {
+                    if let Some(entry) = self.entries.lock().unwrap().get_mut(&key) {
+                        entry.last_checked = Instant::now();
+                    }
+                    return Ok(connection);
+                }
+                log!(debug, device = %connection.link.id, "pooled connection failed health check");
+                self.entries.lock().unwrap().remove(&key);
+            }
+            None => {}
+        }
+        let connection = Arc::new(target.clone().connect_with(self.options.clone()).await?);
+        let now = Instant::now();
+        self.entries.lock().unwrap().insert(
+            key,
+            Entry {
+                connection: connection.clone(),
+                last_used: now,
+                last_checked: now,
+            },
+        );
+        Ok(connection)
+    }
+
+    pub fn evict(&self, target: &Target) {
+        self.entries.lock().unwrap().remove(&target.key());
+    }
+
+    pub fn prune(&self) {
+        let idle_timeout = self.idle_timeout;
+        self.entries.lock().unwrap().retain(|_, entry| {
+            Arc::strong_count(&entry.connection) > 1 || entry.last_used.elapsed() < idle_timeout
+        });
+    }
+
+    pub fn connections(&self) -> Vec<Arc<Connection>> {
+        self.entries
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.connection.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
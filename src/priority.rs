@@ -0,0 +1,110 @@
+use std::{cmp::Reverse, collections::BTreeMap, sync::Mutex};
+
+use futures::channel::oneshot;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum Preemption {
+    #[default]
+    Immediate,
+    ChunkBoundary,
+}
+
+#[derive(Default)]
+struct State {
+    busy: bool,
+    next: u64,
+    waiting: BTreeMap<(Reverse<Priority>, u64), oneshot::Sender<()>>,
+}
+
+#[derive(Default)]
+pub(crate) struct Turns {
+    state: Mutex<State>,
+}
+
+pub(crate) struct Turn<'a> {
+    turns: &'a Turns,
+}
+
+struct Waiting<'a> {
+    turns: &'a Turns,
+    receiver: Option<oneshot::Receiver<()>>,
+}
+
+impl Turns {
+    pub(crate) async fn acquire(&self, priority: Priority) -> Turn<'_> {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if !state.busy {
+                state.busy = true;
+                return Turn { turns: self };
+            }
+            let (sender, receiver) = oneshot::channel();
+            let seq = state.next;
+            state.next += 1;
+            state.waiting.insert((Reverse(priority), seq), sender);
+            receiver
+        };
+        let mut waiting = Waiting {
+            turns: self,
+            receiver: Some(receiver),
+        };
+        let _ = waiting.receiver.as_mut().unwrap().await;
+        waiting.receiver = None;
+        Turn { turns: self }
+    }
+
+    pub(crate) fn busy(&self) -> bool {
+        self.state.lock().unwrap().busy
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.waiting.len() + usize::from(state.busy)
+    }
+
+    pub(crate) fn outranked(&self, priority: Priority) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .waiting
+            .keys()
+            .next()
+            .is_some_and(|(Reverse(waiting), _)| *waiting > priority)
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        while let Some((_, sender)) = state.waiting.pop_first() {
+            if sender.send(()).is_ok() {
+                return;
+            }
+        }
+        state.busy = false;
+    }
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        self.turns.release();
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(mut receiver) = self.receiver.take() {
+            if let Ok(Some(())) = receiver.try_recv() {
+                self.turns.release();
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::{
+    net::{SocketAddr, TcpStream},
+    time::{Duration, Instant},
+};
+
+use async_io::Async;
+
+use crate::{with_timeout, DeviceId, Error, Target};
+
+#[derive(Debug, Clone)]
+pub struct ProbeInfo {
+    pub addr: SocketAddr,
+    pub connect_latency: Duration,
+    pub handshake_latency: Option<Duration>,
+    pub certificate: Option<Vec<u8>>,
+}
+
+impl Target {
+    pub async fn probe(&self, timeout: Duration) -> Result<ProbeInfo, Error> {
+        let id = DeviceId {
+            name: self.name.clone(),
+            addr: self.addr,
+        };
+        with_timeout(Some(timeout), &id, "probe", probe(self.addr)).await
+    }
+}
+
+async fn probe(addr: SocketAddr) -> Result<ProbeInfo, Error> {
+    let start = Instant::now();
+    let tcp = Async::<TcpStream>::connect(addr).await?;
+    let connect_latency = start.elapsed();
+    let start = Instant::now();
+    let stream = async_native_tls::TlsConnector::new()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .connect(addr.ip().to_string(), tcp)
+        .await?;
+    let handshake_latency = start.elapsed();
+    let certificate = match stream.peer_certificate()? {
+        Some(certificate) => Some(certificate.to_der()?),
+        None => None,
+    };
+    Ok(ProbeInfo {
+        addr,
+        connect_latency,
+        handshake_latency: Some(handshake_latency),
+        certificate,
+    })
+}
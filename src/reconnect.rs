@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use async_io::Timer;
+use futures::Future;
+
+use crate::{device::Device, with_timeout, Connection, Error, Link, Priority};
+
+impl Link {
+    pub(crate) fn device(&self) -> Device {
+        self.device.lock().unwrap().clone()
+    }
+
+    /// Takes a turn on the command queue for background work such as heartbeats and teardown,
+    /// which must not interleave with commands but should not retry or reconnect either.
+    pub(crate) async fn command<T, F, Fut>(&self, op: F) -> T
+    where
+        F: FnOnce(Device) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let _turn = self.commands.acquire(Priority::Normal).await;
+        op(self.device()).await
+    }
+}
+
+impl Connection {
+    pub async fn ensure_connected(&self) -> Result<(), Error> {
+        let ping = self
+            .link
+            .command(|device| async move { device.ping().await })
+            .await;
+        if ping.is_err() {
+            self.reconnect().await?;
+        }
+        Ok(())
+    }
+
+    async fn reconnect(&self) -> Result<(), Error> {
+        let id = &self.link.id;
+        let options = &self.link.options;
+        let device = with_timeout(
+            options.timeout,
+            id,
+            "connect",
+            Device::open(id.addr, options),
+        )
+        .await
+        .map_err(|e| e.on(id, |device, source| Error::Connect { device, source }))?;
+        #[cfg(feature = "message-tap")]
+        device.forward_to(&self.link.tap).await;
+        *self.link.device.lock().unwrap() = device;
+        self.session.lock().unwrap().take();
+        Ok(())
+    }
+
+    pub(crate) async fn run<T, F, Fut>(&self, op: F) -> Result<T, Error>
+    where
+        F: Fn(Device) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.run_timed("command", self.settings.timeouts.command, true, op)
+            .await
+    }
+
+    /// Like `run`, but for commands that must not be repeated, such as launching an app or
+    /// loading media: a failure is returned rather than retried, so nothing plays twice.
+    pub(crate) async fn run_once<T, F, Fut>(
+        &self,
+        operation: &'static str,
+        timeout: Option<Duration>,
+        op: F,
+    ) -> Result<T, Error>
+    where
+        F: Fn(Device) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.run_timed(operation, timeout, false, op).await
+    }
+
+    /// Commands on one device never overlap: each call waits for the previous one to finish,
+    /// and waiting calls are admitted in the order they arrived.
+    async fn run_timed<T, F, Fut>(
+        &self,
+        operation: &'static str,
+        timeout: Option<Duration>,
+        idempotent: bool,
+        op: F,
+    ) -> Result<T, Error>
+    where
+        F: Fn(Device) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let _turn = self.link.commands.acquire(Priority::Normal).await;
+        let result = self.recover(operation, timeout, idempotent, op).await;
+        self.link.health.lock().unwrap().record(&result);
+        result
+    }
+
+    async fn recover<T, F, Fut>(
+        &self,
+        operation: &'static str,
+        timeout: Option<Duration>,
+        idempotent: bool,
+        op: F,
+    ) -> Result<T, Error>
+    where
+        F: Fn(Device) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let result = with_timeout(timeout, &self.link.id, operation, op(self.device())).await;
+            if let Err(Error::Timeout { .. }) = &result {
+                log!(warn, device = %self.link.id, operation, "operation timed out, reconnecting");
+                self.device().shutdown();
+                let _ = self.reconnect().await;
+            }
+            match result {
+                Err(e)
+                    if idempotent
+                        && (e.is_connection_error() || e.is_transient())
+                        && attempt < self.retry.attempts =>
+                {
+                    attempt += 1;
+                    log!(warn, device = %self.link.id, operation, attempt, error = %e, "retrying command");
+                    Timer::after(self.retry.delay(attempt)).await;
+                    if e.is_connection_error() {
+                        let _ = self.reconnect().await;
+                    }
+                }
+                Err(e) if e.is_connection_error() => {
+                    return Err(e.on(&self.link.id, |device, source| Error::Disconnected {
+                        device,
+                        source,
+                    }))
+                }
+                Err(e) => {
+                    return Err(e.on(&self.link.id, |device, source| Error::Command {
+                        device,
+                        source,
+                    }))
+                }
+                Ok(value) => return Ok(value),
+            }
+        }
+    }
+}
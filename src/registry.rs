@@ -0,0 +1,266 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use async_io::Timer;
+use futures::{pin_mut, StreamExt};
+use serde_json::{json, Value};
+
+use crate::{
+    discover_for, filter::normalize_uuid, info::string, runtime::unblock, ConnectOptions,
+    Connection, DeviceEvent, DiscoverOptions, DiscoveryFilter, Error, Target,
+};
+
+const CACHED_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CachedDevice {
+    pub name: String,
+    pub uuid: Option<String>,
+    pub model: Option<String>,
+    pub addr: SocketAddr,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub host: Option<String>,
+}
+
+impl From<&Target> for CachedDevice {
+    fn from(target: &Target) -> Self {
+        CachedDevice {
+            name: target.name.clone(),
+            uuid: target.uuid.clone(),
+            model: target.model.clone(),
+            addr: target.addr,
+            host: target.host.clone(),
+        }
+    }
+}
+
+impl CachedDevice {
+    pub fn target(&self) -> Target {
+        let mut target = Target::new(self.name.as_str(), self.addr);
+        target.uuid = self.uuid.clone();
+        target.model = self.model.clone();
+        target.host = self.host.clone();
+        target
+    }
+
+    pub(crate) fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "uuid": self.uuid,
+            "model": self.model,
+            "addr": self.addr.to_string(),
+            "host": self.host,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(CachedDevice {
+            name: string(value, "name")?,
+            uuid: string(value, "uuid"),
+            model: string(value, "model"),
+            addr: value["addr"].as_str()?.parse().ok()?,
+            host: string(value, "host"),
+        })
+    }
+}
+
+pub struct Registry {
+    path: PathBuf,
+    options: ConnectOptions,
+    devices: Mutex<HashMap<String, CachedDevice>>,
+}
+
+impl Registry {
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self, Error> {
+        let path = path.into();
+        let devices = match fs::read(&path) {
+            Ok(data) => match serde_json::from_slice(&data)? {
+                Value::Array(devices) => devices
+                    .iter()
+                    .map(CachedDevice::from_json)
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(Error::InvalidRecord("registry"))?,
+                _ => return Err(Error::InvalidRecord("registry")),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Registry {
+            path,
+            options: ConnectOptions::default(),
+            devices: Mutex::new(
+                devices
+                    .into_iter()
+                    .map(|device| (key(&device.name), device))
+                    .collect(),
+            ),
+        })
+    }
+
+    pub fn with_options(mut self, options: ConnectOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn devices(&self) -> Vec<CachedDevice> {
+        self.devices.lock().unwrap().values().cloned().collect()
+    }
+
+    pub(crate) fn to_json(&self) -> Value {
+        Value::Array(self.devices().iter().map(CachedDevice::to_json).collect())
+    }
+
+    pub fn cached(&self, name: &str) -> Option<CachedDevice> {
+        self.devices.lock().unwrap().get(&key(name)).cloned()
+    }
+
+    pub async fn get(&self, name: &str) -> Result<Connection, Error> {
+        let cached = self.cached(name);
+        if let Some(device) = &cached {
+            let mut options = self.options.clone();
+            options.timeout = options.timeout.or(Some(CACHED_CONNECT_TIMEOUT));
+            let target = device.target();
+            if let Ok(connection) = target.clone().connect_with(options.clone()).await {
+                return Ok(connection);
+            }
+            if target.host.is_some() {
+                if let Ok(connection) = target.connect_by_host(options).await {
+                    if let Some(device) = self.devices.lock().unwrap().get_mut(&key(name)) {
+                        device.addr = connection.device_id().addr;
+                    }
+                    self.save().await?;
+                    return Ok(connection);
+                }
+            }
+        }
+        let filter = match cached.and_then(|device| device.uuid) {
+            Some(uuid) => DiscoveryFilter::new().uuid(uuid),
+            None => DiscoveryFilter::new().name(name),
+        };
+        let targets = DiscoverOptions::new()
+            .filter(filter)
+            .discover()
+            .take_until(Timer::after(LOOKUP_TIMEOUT));
+        pin_mut!(targets);
+        let target = targets
+            .next()
+            .await
+            .unwrap_or_else(|| Err(Error::DeviceNotFound(name.to_string())))?;
+        self.insert(&target);
+        self.save().await?;
+        target.connect_with(self.options.clone()).await
+    }
+
+    pub async fn refresh(&self, duration: Duration) -> Result<(), Error> {
+        let targets = discover_for(duration);
+        pin_mut!(targets);
+        while let Some(target) = targets.next().await {
+            self.insert(&target?);
+        }
+        self.save().await
+    }
+
+    pub async fn apply(&self, event: &DeviceEvent) -> Result<bool, Error> {
+        let changed = match event {
+            DeviceEvent::Added(target) => {
+                let cached = CachedDevice::from(target);
+                let stale = target.uuid.as_deref().is_some_and(|uuid| {
+                    self.devices
+                        .lock()
+                        .unwrap()
+                        .values()
+                        .any(|device| same_uuid(device, uuid) && *device != cached)
+                });
+                if stale {
+                    self.insert(target);
+                }
+                stale
+            }
+            DeviceEvent::Renamed { uuid, new, .. } => self.rename(uuid, new),
+            DeviceEvent::Removed { .. } => false,
+        };
+        if changed {
+            self.save().await?;
+        }
+        Ok(changed)
+    }
+
+    pub async fn save(&self) -> Result<(), Error> {
+        let data = serde_json::to_vec_pretty(&self.to_json())?;
+        let path = self.path.clone();
+        unblock(move || fs::write(path, data)).await?;
+        Ok(())
+    }
+
+    fn insert(&self, target: &Target) {
+        let mut devices = self.devices.lock().unwrap();
+        devices.retain(|_, device| target.uuid.is_none() || device.uuid != target.uuid);
+        devices.insert(key(&target.name), target.into());
+    }
+
+    fn rename(&self, uuid: &str, name: &str) -> bool {
+        let mut devices = self.devices.lock().unwrap();
+        let old = devices
+            .iter()
+            .find(|(_, device)| same_uuid(device, uuid))
+            .map(|(key, _)| key.clone());
+        match old.and_then(|old| devices.remove(&old)) {
+            Some(mut device) => {
+                device.name = name.to_string();
+                devices.insert(key(name), device);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn key(name: &str) -> String {
+    name.to_lowercase()
+}
+
+fn same_uuid(device: &CachedDevice, uuid: &str) -> bool {
+    device
+        .uuid
+        .as_deref()
+        .is_some_and(|cached| normalize_uuid(cached) == normalize_uuid(uuid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_devices_saved_without_a_host() {
+        let value = json!({
+            "name": "Kitchen",
+            "uuid": null,
+            "model": "Google Home",
+            "addr": "192.168.1.20:8009",
+        });
+        let device = CachedDevice::from_json(&value).unwrap();
+        assert_eq!(device.addr, "192.168.1.20:8009".parse().unwrap());
+        assert_eq!(device.model.as_deref(), Some("Google Home"));
+        assert!(device.uuid.is_none() && device.host.is_none());
+        assert_eq!(CachedDevice::from_json(&device.to_json()), Some(device));
+    }
+
+    #[test]
+    fn rejects_devices_without_an_address() {
+        assert!(CachedDevice::from_json(&json!({ "name": "Kitchen" })).is_none());
+        let value = json!({ "name": "Kitchen", "addr": "kitchen.local" });
+        assert!(CachedDevice::from_json(&value).is_none());
+    }
+}
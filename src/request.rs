@@ -0,0 +1,33 @@
+use serde_json::{json, Value};
+
+use crate::{info::string, DeviceStatus, SayOptions};
+
+pub(crate) fn parse(payload: &[u8]) -> (String, SayOptions) {
+    let request = serde_json::from_slice::<Value>(payload)
+        .ok()
+        .and_then(|request| Some((string(&request, "message")?, request)));
+    let (message, request) = match request {
+        Some(request) => request,
+        None => {
+            let message = String::from_utf8_lossy(payload).trim().to_owned();
+            return (message, SayOptions::default());
+        }
+    };
+    let options = SayOptions {
+        lang: string(&request, "lang"),
+        slow: request["slow"].as_bool().unwrap_or(false),
+        volume: request["volume"].as_f64().map(|volume| volume as f32),
+        voice: string(&request, "voice"),
+        ..SayOptions::default()
+    };
+    (message, options)
+}
+
+pub(crate) fn summary(status: DeviceStatus) -> Value {
+    json!({
+        "app": status.app.map(|app| app.display_name),
+        "volume": status.volume,
+        "muted": status.muted,
+        "standby": status.standby,
+    })
+}
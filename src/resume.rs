@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+
+use crate::{AppStatus, Connection, Error, SayOptions, DEFAULT_MEDIA_RECEIVER};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResumePolicy {
+    #[default]
+    Keep,
+    Stop,
+    Relaunch,
+}
+
+impl Connection {
+    pub async fn say_and_resume<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        policy: ResumePolicy,
+    ) -> Result<(), Error> {
+        let options = SayOptions {
+            resume: Some(policy),
+            ..SayOptions::default()
+        };
+        self.speak(message, options, self.provider.clone(), true)
+            .await
+    }
+
+    pub(crate) async fn foreground_app(&self) -> Result<Option<AppStatus>, Error> {
+        Ok(self
+            .run(|device| async move { device.receiver_status().await })
+            .await?
+            .applications
+            .into_iter()
+            .next()
+            .filter(|app| app.app_id != DEFAULT_MEDIA_RECEIVER))
+    }
+
+    pub(crate) async fn restore(
+        &self,
+        previous: Option<AppStatus>,
+        policy: ResumePolicy,
+    ) -> Result<(), Error> {
+        if policy == ResumePolicy::Keep {
+            return Ok(());
+        }
+        let session = self.session.lock().unwrap().take();
+        let (session, previous) = (&session, &previous);
+        self.run_once(
+            "launch",
+            self.settings.timeouts.command,
+            move |device| async move {
+                if let Some(session) = session.as_ref().filter(|session| session.launched) {
+                    device.stop_app(&session.session_id).await?;
+                }
+                if let (ResumePolicy::Relaunch, Some(app)) = (policy, previous) {
+                    device.launch(&app.app_id).await?;
+                }
+                Ok(())
+            },
+        )
+        .await
+    }
+}
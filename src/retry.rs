@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use crate::{Connection, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 1,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(attempts: u32) -> Self {
+        RetryPolicy {
+            attempts,
+            ..Self::default()
+        }
+    }
+
+    pub fn never() -> Self {
+        Self::new(0)
+    }
+
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.);
+        self
+    }
+
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0., 1.);
+        self
+    }
+
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = self.backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let base = base.min(self.max_backoff.as_secs_f64());
+        let spread = (fastrand::f64() * 2. - 1.) * self.jitter;
+        Duration::from_secs_f64((base * (1. + spread)).max(0.))
+    }
+}
+
+impl Connection {
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+}
+
+impl Error {
+    pub(crate) fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Error::Protocol(_) | Error::Command { .. } | Error::Timeout { .. }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_grows_exponentially_without_jitter() {
+        let policy = RetryPolicy::new(5)
+            .backoff(Duration::from_millis(100))
+            .jitter(0.);
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn delay_is_capped_by_max_backoff() {
+        let policy = RetryPolicy::new(5)
+            .backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(3))
+            .jitter(0.);
+        assert_eq!(policy.delay(2), Duration::from_secs(2));
+        assert_eq!(policy.delay(3), Duration::from_secs(3));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn delay_jitter_stays_within_spread() {
+        let policy = RetryPolicy::new(5)
+            .backoff(Duration::from_secs(1))
+            .jitter(0.5);
+        for _ in 0..100 {
+            let delay = policy.delay(1);
+            assert!(delay >= Duration::from_millis(500));
+            assert!(delay <= Duration::from_millis(1500));
+        }
+    }
+
+    #[test]
+    fn builders_clamp_their_inputs() {
+        let policy = RetryPolicy::new(1).multiplier(0.5).jitter(4.);
+        assert_eq!(policy.multiplier, 1.);
+        assert_eq!(policy.jitter, 1.);
+    }
+}
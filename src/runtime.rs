@@ -0,0 +1,80 @@
+#[cfg(any(feature = "tokio", not(feature = "async-std")))]
+use std::{pin::Pin, sync::OnceLock, thread};
+
+use futures::Future;
+#[cfg(any(feature = "tokio", not(feature = "async-std")))]
+use futures::{channel::mpsc, StreamExt};
+
+use crate::Error;
+
+#[cfg(any(feature = "tokio", not(feature = "async-std")))]
+type Task = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+#[cfg(feature = "tokio")]
+pub(crate) async fn unblock<T, E, F>(f: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    E: Into<Error> + Send + 'static,
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+{
+    let handle = match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle,
+        Err(_) => return ::blocking::unblock(f).await.map_err(Into::into),
+    };
+    match handle.spawn_blocking(f).await {
+        Ok(result) => result.map_err(Into::into),
+        Err(e) if e.is_cancelled() => Err(Error::Cancelled),
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) async fn unblock<T, E, F>(f: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    E: Into<Error> + Send + 'static,
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+{
+    async_std::task::spawn_blocking(f).await.map_err(Into::into)
+}
+
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+pub(crate) async fn unblock<T, E, F>(f: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    E: Into<Error> + Send + 'static,
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+{
+    ::blocking::unblock(f).await.map_err(Into::into)
+}
+
+#[cfg(feature = "tokio")]
+pub(crate) fn spawn<F: Future<Output = ()> + Send + 'static>(future: F) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => drop(handle.spawn(future)),
+        Err(_) => detach(future),
+    }
+}
+
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) fn spawn<F: Future<Output = ()> + Send + 'static>(future: F) {
+    drop(async_std::task::spawn(future));
+}
+
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+pub(crate) fn spawn<F: Future<Output = ()> + Send + 'static>(future: F) {
+    detach(future);
+}
+
+/// Background tasks started without a runtime share one thread, so they must only ever wait
+/// on I/O or timers.
+#[cfg(any(feature = "tokio", not(feature = "async-std")))]
+fn detach<F: Future<Output = ()> + Send + 'static>(future: F) {
+    static TASKS: OnceLock<mpsc::UnboundedSender<Task>> = OnceLock::new();
+    let tasks = TASKS.get_or_init(|| {
+        let (sender, receiver) = mpsc::unbounded::<Task>();
+        thread::spawn(move || async_io::block_on(receiver.for_each_concurrent(None, |task| task)));
+        sender
+    });
+    let _ = tasks.unbounded_send(Box::pin(future));
+}
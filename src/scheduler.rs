@@ -0,0 +1,461 @@
+use std::{
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::Duration,
+};
+
+use async_io::Timer;
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Timelike,
+};
+
+use crate::{Error, Registry, SayOptions};
+
+const TICK: Duration = Duration::from_secs(1);
+const RETRY_DELAY: i64 = 30;
+const MAX_ATTEMPTS: u32 = 3;
+const SEARCH_DAYS: i64 = 366 * 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days: Vec<u32>,
+    months: Vec<u32>,
+    weekdays: Vec<u32>,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    pub fn parse(expr: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidSchedule(expr.to_string());
+        let fields: Vec<_> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(invalid());
+        }
+        let mut weekdays = field(fields[4], 0..=7).ok_or_else(invalid)?;
+        for day in &mut weekdays {
+            *day %= 7;
+        }
+        Ok(Cron {
+            minutes: field(fields[0], 0..=59).ok_or_else(invalid)?,
+            hours: field(fields[1], 0..=23).ok_or_else(invalid)?,
+            days: field(fields[2], 1..=31).ok_or_else(invalid)?,
+            months: field(fields[3], 1..=12).ok_or_else(invalid)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    pub fn next_after(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start =
+            time.naive_local().with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        let mut date = start.date();
+        for _ in 0..SEARCH_DAYS {
+            if self.months.contains(&date.month()) && self.matches_day(date) {
+                for &hour in &self.hours {
+                    for &minute in &self.minutes {
+                        let candidate = match date.and_hms_opt(hour, minute, 0) {
+                            Some(candidate) if candidate >= start => candidate,
+                            _ => continue,
+                        };
+                        if let Some(time) = Local.from_local_datetime(&candidate).earliest() {
+                            return Some(time);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.days.contains(&date.day());
+        let weekday = self
+            .weekdays
+            .contains(&date.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+fn field(spec: &str, bounds: RangeInclusive<u32>) -> Option<Vec<u32>> {
+    let mut values = Vec::new();
+    for part in spec.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse().ok().filter(|step| *step > 0)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (*bounds.start(), *bounds.end()),
+            range => match range.split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                None => {
+                    let value = range.parse().ok()?;
+                    (value, value)
+                }
+            },
+        };
+        if !bounds.contains(&start) || !bounds.contains(&end) || start > end {
+            return None;
+        }
+        values.extend((start..=end).step_by(step));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Some(values)
+}
+
+#[derive(Debug, Clone)]
+enum Schedule {
+    At(DateTime<Local>),
+    Cron(Cron),
+}
+
+struct Job {
+    id: u64,
+    schedule: Schedule,
+    device: String,
+    message: String,
+    options: SayOptions,
+    next: Option<DateTime<Local>>,
+    attempts: u32,
+}
+
+type Jobs = Arc<Mutex<Vec<Job>>>;
+
+pub struct JobHandle {
+    id: u64,
+    jobs: Weak<Mutex<Vec<Job>>>,
+}
+
+impl JobHandle {
+    pub fn cancel(&self) {
+        if let Some(jobs) = self.jobs.upgrade() {
+            jobs.lock().unwrap().retain(|job| job.id != self.id);
+        }
+    }
+
+    pub fn next_run(&self) -> Option<DateTime<Local>> {
+        let jobs = self.jobs.upgrade()?;
+        let jobs = jobs.lock().unwrap();
+        jobs.iter().find(|job| job.id == self.id)?.next
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.next_run().is_some()
+    }
+}
+
+pub struct Scheduler {
+    registry: Arc<Registry>,
+    jobs: Jobs,
+    next_id: AtomicU64,
+}
+
+impl Scheduler {
+    pub fn new(registry: Arc<Registry>) -> Self {
+        Scheduler {
+            registry,
+            jobs: Arc::new(Mutex::new(Vec::new())),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    pub fn at<T: Into<String>, U: Into<String>>(
+        &self,
+        time: DateTime<Local>,
+        device: T,
+        message: U,
+    ) -> JobHandle {
+        self.at_with_options(time, device, message, SayOptions::default())
+    }
+
+    pub fn at_with_options<T: Into<String>, U: Into<String>>(
+        &self,
+        time: DateTime<Local>,
+        device: T,
+        message: U,
+        options: SayOptions,
+    ) -> JobHandle {
+        self.schedule(Schedule::At(time), device, message, options)
+    }
+
+    pub fn cron<T: Into<String>, U: Into<String>>(
+        &self,
+        expr: &str,
+        device: T,
+        message: U,
+    ) -> Result<JobHandle, Error> {
+        self.cron_with_options(expr, device, message, SayOptions::default())
+    }
+
+    pub fn cron_with_options<T: Into<String>, U: Into<String>>(
+        &self,
+        expr: &str,
+        device: T,
+        message: U,
+        options: SayOptions,
+    ) -> Result<JobHandle, Error> {
+        let cron = Cron::parse(expr)?;
+        Ok(self.schedule(Schedule::Cron(cron), device, message, options))
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub async fn run(&self) {
+        loop {
+            let now = Local::now();
+            let due: Vec<_> = self
+                .jobs
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|job| job.next.is_some_and(|next| next <= now))
+                .map(|job| {
+                    (
+                        job.id,
+                        job.device.clone(),
+                        job.message.clone(),
+                        job.options.clone(),
+                    )
+                })
+                .collect();
+            for (id, device, message, options) in due {
+                let result = self.deliver(&device, &message, options).await;
+                if let Err(_e) = &result {
+                    log!(warn, %device, error = %_e, "scheduled announcement failed");
+                }
+                self.advance(id, result.is_ok());
+            }
+            Timer::after(TICK).await;
+        }
+    }
+
+    fn schedule<T: Into<String>, U: Into<String>>(
+        &self,
+        schedule: Schedule,
+        device: T,
+        message: U,
+        options: SayOptions,
+    ) -> JobHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let next = match &schedule {
+            Schedule::At(time) => Some(*time),
+            Schedule::Cron(cron) => cron.next_after(Local::now()),
+        };
+        self.jobs.lock().unwrap().push(Job {
+            id,
+            schedule,
+            device: device.into(),
+            message: message.into(),
+            options,
+            next,
+            attempts: 0,
+        });
+        JobHandle {
+            id,
+            jobs: Arc::downgrade(&self.jobs),
+        }
+    }
+
+    async fn deliver(&self, device: &str, message: &str, options: SayOptions) -> Result<(), Error> {
+        let connection = self.registry.get(device).await?;
+        connection.say_with_options(message, options).await?;
+        Ok(())
+    }
+
+    fn advance(&self, id: u64, delivered: bool) {
+        let now = Local::now();
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = match jobs.iter_mut().find(|job| job.id == id) {
+            Some(job) => job,
+            None => return,
+        };
+        job.attempts += 1;
+        if !delivered && job.attempts < MAX_ATTEMPTS {
+            job.next = Some(now + ChronoDuration::seconds(RETRY_DELAY));
+            return;
+        }
+        job.attempts = 0;
+        job.next = match &job.schedule {
+            Schedule::At(_) => None,
+            Schedule::Cron(cron) => cron.next_after(now),
+        };
+        jobs.retain(|job| job.next.is_some());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        let time = NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .unwrap();
+        Local.from_local_datetime(&time).unwrap()
+    }
+
+    fn scheduler() -> Scheduler {
+        let path = std::env::temp_dir().join("cast-notify-scheduler-test-registry.json");
+        Scheduler::new(Arc::new(Registry::open(path).unwrap()))
+    }
+
+    fn attempts(scheduler: &Scheduler, handle: &JobHandle) -> u32 {
+        let jobs = scheduler.jobs.lock().unwrap();
+        jobs.iter()
+            .find(|job| job.id == handle.id)
+            .unwrap()
+            .attempts
+    }
+
+    fn assert_retry_scheduled(handle: &JobHandle, before: DateTime<Local>) {
+        let retry = ChronoDuration::seconds(RETRY_DELAY);
+        let next = handle.next_run().unwrap();
+        assert!(next >= before + retry && next <= Local::now() + retry);
+    }
+
+    #[test]
+    fn parse_expands_ranges_lists_and_steps() {
+        let cron = Cron::parse("*/15 9-11,17 * * 1-5").unwrap();
+        assert_eq!(cron.minutes, vec![0, 15, 30, 45]);
+        assert_eq!(cron.hours, vec![9, 10, 11, 17]);
+        assert_eq!(cron.weekdays, vec![1, 2, 3, 4, 5]);
+        assert!(cron.any_day);
+        assert!(!cron.any_weekday);
+        assert_eq!(Cron::parse("0 0 * * 7").unwrap().weekdays, vec![0]);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_expressions() {
+        for expr in [
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+            "0 0 0 * *",
+        ] {
+            assert!(
+                matches!(Cron::parse(expr), Err(Error::InvalidSchedule(_))),
+                "{}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn next_after_finds_the_following_minute() {
+        let cron = Cron::parse("30 9 * * *").unwrap();
+        assert_eq!(
+            cron.next_after(local(2021, 1, 4, 8, 0)),
+            Some(local(2021, 1, 4, 9, 30))
+        );
+        assert_eq!(
+            cron.next_after(local(2021, 1, 4, 9, 30)),
+            Some(local(2021, 1, 5, 9, 30))
+        );
+    }
+
+    #[test]
+    fn next_after_matches_weekdays() {
+        let cron = Cron::parse("0 12 * * 1").unwrap();
+        assert_eq!(
+            cron.next_after(local(2021, 1, 6, 10, 0)),
+            Some(local(2021, 1, 11, 12, 0))
+        );
+    }
+
+    #[test]
+    fn next_after_matches_day_or_weekday() {
+        let cron = Cron::parse("0 0 13 * 5").unwrap();
+        assert_eq!(
+            cron.next_after(local(2021, 1, 1, 0, 0)),
+            Some(local(2021, 1, 8, 0, 0))
+        );
+        assert_eq!(
+            cron.next_after(local(2021, 1, 8, 0, 0)),
+            Some(local(2021, 1, 13, 0, 0))
+        );
+    }
+
+    #[test]
+    fn failed_one_off_jobs_are_retried_then_dropped() {
+        let scheduler = scheduler();
+        let handle = scheduler.at(local(2021, 1, 1, 0, 0), "Kitchen", "Door open");
+        for attempt in 1..MAX_ATTEMPTS {
+            let before = Local::now();
+            scheduler.advance(handle.id, false);
+            assert_retry_scheduled(&handle, before);
+            assert_eq!(attempts(&scheduler, &handle), attempt);
+        }
+        scheduler.advance(handle.id, false);
+        assert!(!handle.is_active());
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn delivered_one_off_jobs_are_dropped() {
+        let scheduler = scheduler();
+        let handle = scheduler.at(local(2021, 1, 1, 0, 0), "Kitchen", "Door open");
+        scheduler.advance(handle.id, false);
+        scheduler.advance(handle.id, true);
+        assert!(!handle.is_active());
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn cron_jobs_move_to_the_next_occurrence_and_reset_attempts() {
+        let scheduler = scheduler();
+        let handle = scheduler
+            .cron("0 0 1 1 *", "Kitchen", "Happy new year")
+            .unwrap();
+        let occurrence = handle.next_run().unwrap();
+        scheduler.advance(handle.id, false);
+        assert_eq!(attempts(&scheduler, &handle), 1);
+        scheduler.advance(handle.id, true);
+        assert_eq!(handle.next_run(), Some(occurrence));
+        assert_eq!(attempts(&scheduler, &handle), 0);
+        for _ in 0..MAX_ATTEMPTS {
+            scheduler.advance(handle.id, false);
+        }
+        assert_eq!(handle.next_run(), Some(occurrence));
+        assert_eq!(attempts(&scheduler, &handle), 0);
+        assert_eq!(scheduler.len(), 1);
+    }
+
+    #[test]
+    fn advancing_a_cancelled_job_does_nothing() {
+        let scheduler = scheduler();
+        let kept = scheduler.at(local(2021, 1, 1, 0, 0), "Kitchen", "Door open");
+        let cancelled = scheduler.at(local(2021, 1, 1, 0, 0), "Hall", "Door open");
+        cancelled.cancel();
+        scheduler.advance(cancelled.id, true);
+        assert_eq!(scheduler.len(), 1);
+        assert!(kept.is_active());
+    }
+
+    #[test]
+    fn next_after_gives_up_on_impossible_dates() {
+        let cron = Cron::parse("0 0 31 2 *").unwrap();
+        assert_eq!(cron.next_after(local(2021, 1, 1, 0, 0)), None);
+    }
+}
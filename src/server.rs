@@ -0,0 +1,262 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    announcement::Ticket, runtime::unblock, transcode::is_native, Clip, Connection, Error,
+    Preemption, Priority, SayOptions, Transcoder, TtsProvider,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+const CLIP_TTL: Duration = Duration::from_secs(600);
+
+struct Audio {
+    body: Arc<Vec<u8>>,
+    content_type: String,
+    expires: Option<Instant>,
+}
+
+struct Inner {
+    addr: SocketAddr,
+    clips: Mutex<HashMap<String, Audio>>,
+    next: AtomicU64,
+    transcoder: Mutex<Option<Arc<dyn Transcoder>>>,
+}
+
+#[derive(Clone)]
+pub struct LocalAudioServer {
+    inner: Arc<Inner>,
+}
+
+impl LocalAudioServer {
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let inner = Arc::new(Inner {
+            addr: listener.local_addr()?,
+            clips: Mutex::new(HashMap::new()),
+            next: AtomicU64::new(0),
+            transcoder: Mutex::new(None),
+        });
+        let server = inner.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let server = server.clone();
+                thread::spawn(move || {
+                    let _ = handle(&server, stream);
+                });
+            }
+        });
+        Ok(LocalAudioServer { inner })
+    }
+
+    pub fn reachable_from(device: SocketAddr) -> io::Result<Self> {
+        let unspecified = match device.ip() {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0))?;
+        socket.connect(device)?;
+        Self::bind(SocketAddr::new(socket.local_addr()?.ip(), 0))
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.inner.addr
+    }
+
+    pub fn serve<T: Into<String>>(&self, bytes: Vec<u8>, content_type: T) -> Clip {
+        let id = self.inner.next.fetch_add(1, Ordering::Relaxed);
+        let expires = Instant::now() + CLIP_TTL;
+        self.insert(
+            &format!("/audio/{}", id),
+            bytes,
+            content_type.into(),
+            Some(expires),
+        )
+    }
+
+    pub fn set_transcoder(&self, transcoder: Option<Arc<dyn Transcoder>>) {
+        *self.inner.transcoder.lock().unwrap() = transcoder;
+    }
+
+    pub fn serve_transcoded<T: Into<String>>(
+        &self,
+        bytes: Vec<u8>,
+        content_type: T,
+    ) -> Result<Clip, Error> {
+        let content_type = content_type.into();
+        let transcoder = self.inner.transcoder.lock().unwrap().clone();
+        match transcoder {
+            Some(transcoder) if !is_native(&content_type) => {
+                let (bytes, content_type) = transcoder.transcode(bytes, &content_type)?;
+                Ok(self.serve(bytes, content_type))
+            }
+            _ => Ok(self.serve(bytes, content_type)),
+        }
+    }
+
+    pub fn remove(&self, clip: &Clip) {
+        if let Some(path) = self.path(clip) {
+            self.inner.clips.lock().unwrap().remove(path);
+        }
+    }
+
+    pub(crate) fn serve_at<T: Into<String>>(
+        &self,
+        path: &str,
+        bytes: Vec<u8>,
+        content_type: T,
+    ) -> Clip {
+        self.insert(path, bytes, content_type.into(), None)
+    }
+
+    /// One-off clips from `serve` expire after `CLIP_TTL`, long enough for a device to fetch
+    /// them, so synthesized speech does not pile up for the life of the server.
+    fn insert(
+        &self,
+        path: &str,
+        bytes: Vec<u8>,
+        content_type: String,
+        expires: Option<Instant>,
+    ) -> Clip {
+        let now = Instant::now();
+        let mut clips = self.inner.clips.lock().unwrap();
+        clips.retain(|_, audio| audio.expires.map_or(true, |expires| expires > now));
+        clips.insert(
+            path.to_string(),
+            Audio {
+                body: Arc::new(bytes),
+                content_type: content_type.clone(),
+                expires,
+            },
+        );
+        Clip {
+            url: format!("http://{}{}", self.inner.addr, path),
+            content_type,
+        }
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Option<Clip> {
+        let clips = self.inner.clips.lock().unwrap();
+        clips.get(path).map(|audio| Clip {
+            url: format!("http://{}{}", self.inner.addr, path),
+            content_type: audio.content_type.clone(),
+        })
+    }
+
+    fn path<'a>(&self, clip: &'a Clip) -> Option<&'a str> {
+        clip.url
+            .strip_prefix(&format!("http://{}", self.inner.addr))
+            .filter(|path| path.starts_with('/'))
+    }
+}
+
+fn handle(server: &Inner, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let mut stream = reader.into_inner();
+    let mut parts = request.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method, path),
+        _ => return write!(stream, "HTTP/1.0 400 Bad Request\r\n\r\n"),
+    };
+    let audio = server
+        .clips
+        .lock()
+        .unwrap()
+        .get(path)
+        .map(|audio| (audio.body.clone(), audio.content_type.clone()));
+    let (body, content_type) = match (method, audio) {
+        ("GET", Some(audio)) | ("HEAD", Some(audio)) => audio,
+        ("GET", None) | ("HEAD", None) => {
+            return write!(stream, "HTTP/1.0 404 Not Found\r\n\r\n");
+        }
+        _ => return write!(stream, "HTTP/1.0 405 Method Not Allowed\r\n\r\n"),
+    };
+    write!(
+        stream,
+        "HTTP/1.0 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        content_type,
+        body.len()
+    )?;
+    if method == "GET" {
+        stream.write_all(&body)?;
+    }
+    stream.flush()
+}
+
+pub struct LocalTts<F> {
+    server: LocalAudioServer,
+    content_type: String,
+    render: F,
+}
+
+impl<F> LocalTts<F>
+where
+    F: Fn(&str, &str) -> Result<Vec<u8>, Error> + Send + Sync,
+{
+    pub fn new<T: Into<String>>(server: LocalAudioServer, content_type: T, render: F) -> Self {
+        LocalTts {
+            server,
+            content_type: content_type.into(),
+            render,
+        }
+    }
+}
+
+impl<F> TtsProvider for LocalTts<F>
+where
+    F: Fn(&str, &str) -> Result<Vec<u8>, Error> + Send + Sync,
+{
+    fn synthesize(
+        &self,
+        message: &str,
+        language: &str,
+        _options: &SayOptions,
+    ) -> Result<Clip, Error> {
+        let bytes = (self.render)(message, language)?;
+        self.server
+            .serve_transcoded(bytes, self.content_type.as_str())
+    }
+}
+
+impl Connection {
+    pub async fn play_bytes<T: Into<String>>(
+        &self,
+        server: &LocalAudioServer,
+        bytes: Vec<u8>,
+        content_type: T,
+    ) -> Result<(), Error> {
+        let content_type = content_type.into();
+        let serving = server.clone();
+        let clip = unblock(move || serving.serve_transcoded(bytes, content_type)).await?;
+        let media = clip.media();
+        let result = self
+            .enqueue(
+                vec![move || Ok(media.clone())],
+                true,
+                Priority::Normal,
+                Preemption::Immediate,
+                None,
+                &Ticket::default(),
+                None,
+            )
+            .await;
+        server.remove(&clip);
+        result
+    }
+}
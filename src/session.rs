@@ -0,0 +1,101 @@
+use crate::{device::Device, AppStatus, Connection, DeviceId, Error, DEFAULT_MEDIA_RECEIVER};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub(crate) transport_id: String,
+    pub(crate) session_id: String,
+    pub(crate) media_session_id: Option<i32>,
+    pub(crate) launched: bool,
+}
+
+impl Session {
+    pub fn transport_id(&self) -> &str {
+        &self.transport_id
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn media_session_id(&self) -> Option<i32> {
+        self.media_session_id
+    }
+}
+
+impl Connection {
+    pub fn session(&self) -> Option<Session> {
+        self.session.lock().unwrap().clone()
+    }
+
+    pub async fn attach(&self) -> Result<Option<Session>, Error> {
+        let session = self
+            .run(|device| async move {
+                let app = match running(&device).await? {
+                    Some(app) => app,
+                    None => return Ok(None),
+                };
+                device.connect(&app.transport_id).await?;
+                let status = device.media_status(&app.transport_id, None).await?;
+                Ok(Some(Session {
+                    media_session_id: status.first().map(|status| status.media_session_id),
+                    transport_id: app.transport_id,
+                    session_id: app.session_id,
+                    launched: false,
+                }))
+            })
+            .await?;
+        *self.session.lock().unwrap() = session.clone();
+        Ok(session)
+    }
+
+    pub async fn launch(&self) -> Result<Session, Error> {
+        let id = &self.link.id;
+        let session = self
+            .run_once(
+                "launch",
+                self.settings.timeouts.command,
+                move |device| async move {
+                    let (app, launched) = media_receiver(&device, id, false).await?;
+                    device.connect(&app.transport_id).await?;
+                    Ok(Session {
+                        transport_id: app.transport_id,
+                        session_id: app.session_id,
+                        media_session_id: None,
+                        launched,
+                    })
+                },
+            )
+            .await?;
+        *self.session.lock().unwrap() = Some(session.clone());
+        Ok(session)
+    }
+}
+
+pub(crate) async fn media_receiver(
+    device: &Device,
+    id: &DeviceId,
+    reuse: bool,
+) -> Result<(AppStatus, bool), Error> {
+    if reuse {
+        if let Some(app) = running(device).await? {
+            return Ok((app, false));
+        }
+    }
+    let app = device
+        .launch(DEFAULT_MEDIA_RECEIVER)
+        .await
+        .map_err(|source| Error::LaunchApp {
+            device: id.clone(),
+            source: Box::new(source),
+        })?;
+    Ok((app, true))
+}
+
+async fn running(device: &Device) -> Result<Option<AppStatus>, Error> {
+    Ok(device
+        .receiver_status()
+        .await?
+        .applications
+        .into_iter()
+        .find(|app| app.app_id == DEFAULT_MEDIA_RECEIVER))
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use crate::{Connection, ResumePolicy};
+
+const DEFAULT_LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(default))]
+pub struct Timeouts {
+    pub command: Option<Duration>,
+    pub load: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            command: None,
+            load: Some(DEFAULT_LOAD_TIMEOUT),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConnectionSettings {
+    pub language: Option<String>,
+    pub volume: Option<f32>,
+    pub resume: ResumePolicy,
+    pub wake: bool,
+    pub batch: bool,
+    pub fallback: bool,
+    pub reuse_session: bool,
+    pub timeouts: Timeouts,
+    #[cfg(feature = "detect-language")]
+    pub detect_language: bool,
+}
+
+impl Connection {
+    pub fn configure(&mut self, settings: ConnectionSettings) {
+        if let Some(language) = &settings.language {
+            self.language = language.clone();
+        }
+        self.settings = settings;
+    }
+
+    pub fn settings(&self) -> &ConnectionSettings {
+        &self.settings
+    }
+}
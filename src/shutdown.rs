@@ -0,0 +1,86 @@
+use std::{thread, time::Duration};
+
+use async_io::{block_on, Timer};
+use serde_json::json;
+
+use crate::{device::Device, with_timeout, Connection, Error, Link, PlayerState, Session};
+
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const DROP_TEARDOWN_LIMIT: Duration = Duration::from_secs(120);
+
+impl Connection {
+    pub async fn disconnect(mut self) -> Result<(), Error> {
+        self.closed = true;
+        let session = self.session.lock().unwrap().take();
+        let destination = self.link.options.destination_id.clone();
+        self.link
+            .command(|device| async move { teardown(&device, session, &destination).await })
+            .await
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+        let session = self.session.lock().unwrap().take();
+        let destination = self.link.options.destination_id.clone();
+        let link = self.link.clone();
+        thread::spawn(move || {
+            let work = async {
+                if let Some(session) = &session {
+                    wait_idle(&link, session).await;
+                }
+                link.command(|device| async move { teardown(&device, session, &destination).await })
+                    .await
+            };
+            let limit = Some(DROP_TEARDOWN_LIMIT);
+            let _ = block_on(with_timeout(limit, &link.id, "teardown", work));
+        });
+    }
+}
+
+async fn wait_idle(link: &Link, session: &Session) {
+    while let Ok(status) = link
+        .command(|device| async move {
+            device
+                .media_status(&session.transport_id, session.media_session_id)
+                .await
+        })
+        .await
+    {
+        match status.first() {
+            Some(status) if !matches!(status.player_state, PlayerState::Idle) => {
+                Timer::after(IDLE_POLL_INTERVAL).await;
+            }
+            _ => return,
+        }
+    }
+}
+
+async fn teardown(
+    device: &Device,
+    session: Option<Session>,
+    destination: &str,
+) -> Result<(), Error> {
+    if let Some(session) = session {
+        if !session.launched {
+            device.disconnect(&session.transport_id).await?;
+            return device.disconnect(destination).await;
+        }
+        if let Some(media_session_id) = session.media_session_id {
+            let _ = device
+                .control(
+                    &session.transport_id,
+                    media_session_id,
+                    json!({ "type": "STOP" }),
+                )
+                .await;
+        }
+        device.stop_app(&session.session_id).await?;
+        device.disconnect(&session.transport_id).await?;
+    }
+    device.disconnect(destination).await?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+use async_io::Timer;
+
+use crate::{Connection, Error, DEFAULT_MEDIA_RECEIVER};
+
+const WAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const WAKE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl Connection {
+    pub async fn is_standby(&self) -> Result<bool, Error> {
+        self.run(|device| async move { Ok(device.receiver_status().await?.standby) })
+            .await
+    }
+
+    pub async fn wake(&self) -> Result<(), Error> {
+        self.wake_within(WAKE_TIMEOUT).await
+    }
+
+    pub async fn wake_within(&self, timeout: Duration) -> Result<(), Error> {
+        if !self.is_standby().await? {
+            return Ok(());
+        }
+        log!(debug, device = %self.link.id, "waking device from standby");
+        let deadline = Instant::now() + timeout;
+        self.run_once(
+            "launch",
+            self.settings.timeouts.command,
+            |device| async move {
+                device.launch(DEFAULT_MEDIA_RECEIVER).await?;
+                Ok(())
+            },
+        )
+        .await
+        .map_err(|_| Error::WakeFailed {
+            device: self.link.id.clone(),
+        })?;
+        while self.is_standby().await? {
+            if Instant::now() >= deadline {
+                return Err(Error::WakeFailed {
+                    device: self.link.id.clone(),
+                });
+            }
+            Timer::after(WAKE_POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+}
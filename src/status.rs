@@ -0,0 +1,50 @@
+use crate::{Connection, Error, MediaStatus};
+
+#[derive(Debug, Clone)]
+pub struct AppStatus {
+    pub app_id: String,
+    pub display_name: String,
+    pub status_text: String,
+    pub session_id: String,
+    pub(crate) transport_id: String,
+    pub(crate) supports_media: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeviceStatus {
+    pub app: Option<AppStatus>,
+    pub volume: Option<f32>,
+    pub muted: bool,
+    pub standby: bool,
+    pub active_input: bool,
+    pub media: Option<MediaStatus>,
+}
+
+impl Connection {
+    pub async fn status(&self) -> Result<DeviceStatus, Error> {
+        self.run(|device| async move {
+            let status = device.receiver_status().await?;
+            let app = status.applications.into_iter().next();
+            let media = match &app {
+                Some(app) if app.supports_media => {
+                    device.connect(&app.transport_id).await?;
+                    device
+                        .media_status(&app.transport_id, None)
+                        .await?
+                        .into_iter()
+                        .next()
+                }
+                _ => None,
+            };
+            Ok(DeviceStatus {
+                app,
+                volume: status.volume,
+                muted: status.muted,
+                standby: status.standby,
+                active_input: status.active_input,
+                media,
+            })
+        })
+        .await
+    }
+}
@@ -0,0 +1,198 @@
+use std::convert::TryFrom;
+
+use serde_json::{json, Value};
+
+use crate::{
+    session::media_receiver, AsyncCastDevice, Connection, Error, Priority, Session,
+    DEFAULT_MEDIA_RECEIVER, NAMESPACE_MEDIA,
+};
+
+const TRACK_ID: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextTrack {
+    pub url: String,
+    pub language: Option<String>,
+    pub name: Option<String>,
+}
+
+impl TextTrack {
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        TextTrack {
+            url: url.into(),
+            language: None,
+            name: None,
+        }
+    }
+
+    pub fn language<T: Into<String>>(mut self, language: T) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn name<T: Into<String>>(mut self, name: T) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    fn to_json(&self, id: u32) -> Value {
+        let mut track = json!({
+            "trackId": id,
+            "type": "TEXT",
+            "subtype": "SUBTITLES",
+            "trackContentId": self.url,
+            "trackContentType": "text/vtt",
+        });
+        if let Some(language) = &self.language {
+            track["language"] = language.as_str().into();
+        }
+        if let Some(name) = &self.name {
+            track["name"] = name.as_str().into();
+        }
+        track
+    }
+}
+
+impl AsyncCastDevice {
+    pub async fn load_with_tracks(
+        &mut self,
+        content_id: &str,
+        content_type: &str,
+        tracks: &[TextTrack],
+    ) -> Result<i64, Error> {
+        let (transport_id, session_id) = self.launch_app(DEFAULT_MEDIA_RECEIVER).await?;
+        self.connect_to(&transport_id).await?;
+        self.load_into(&transport_id, &session_id, content_id, content_type, tracks)
+            .await
+    }
+
+    pub async fn edit_tracks(
+        &mut self,
+        transport_id: &str,
+        media_session_id: i64,
+        active: &[u32],
+    ) -> Result<(), Error> {
+        self.request(
+            transport_id,
+            NAMESPACE_MEDIA,
+            json!({
+                "type": "EDIT_TRACKS_INFO",
+                "mediaSessionId": media_session_id,
+                "activeTrackIds": active,
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn load_into(
+        &mut self,
+        transport_id: &str,
+        session_id: &str,
+        content_id: &str,
+        content_type: &str,
+        tracks: &[TextTrack],
+    ) -> Result<i64, Error> {
+        let status = self
+            .request(
+                transport_id,
+                NAMESPACE_MEDIA,
+                load_request(session_id, content_id, content_type, tracks),
+            )
+            .await?;
+        media_session_id(&status)
+    }
+}
+
+impl Connection {
+    pub async fn play_url_with_subtitles<T: Into<String>, U: Into<String>>(
+        &self,
+        url: T,
+        content_type: U,
+        track: TextTrack,
+    ) -> Result<(), Error> {
+        if !self.supports_video() {
+            return Err(Error::Unsupported("subtitles"));
+        }
+        let (url, content_type) = (url.into(), content_type.into());
+        let _turn = self.queue.acquire(Priority::Normal).await;
+        self.wait_idle().await?;
+        let (id, tracks) = (&self.link.id, &[track]);
+        let (url, content_type) = (&url, &content_type);
+        let session = self
+            .run_once(
+                "load",
+                self.settings.timeouts.load,
+                move |device| async move {
+                    let (app, launched) = media_receiver(&device, id, false).await?;
+                    device.connect(&app.transport_id).await?;
+                    let status = device
+                        .request(
+                            &app.transport_id,
+                            NAMESPACE_MEDIA,
+                            load_request(&app.session_id, url, content_type, tracks),
+                        )
+                        .await?;
+                    Ok(Session {
+                        media_session_id: i32::try_from(media_session_id(&status)?).ok(),
+                        transport_id: app.transport_id,
+                        session_id: app.session_id,
+                        launched,
+                    })
+                },
+            )
+            .await?;
+        *self.session.lock().unwrap() = Some(session);
+        Ok(())
+    }
+
+    pub async fn show_subtitles(&self, visible: bool) -> Result<(), Error> {
+        let session = self.session().ok_or(Error::NoActiveMedia)?;
+        let media_session_id = session.media_session_id.ok_or(Error::NoActiveMedia)?;
+        let active: &[u32] = if visible { &[TRACK_ID] } else { &[] };
+        let transport_id = &session.transport_id;
+        self.run(move |device| async move {
+            device.connect(transport_id).await?;
+            device
+                .control(
+                    transport_id,
+                    media_session_id,
+                    json!({ "type": "EDIT_TRACKS_INFO", "activeTrackIds": active }),
+                )
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+fn load_request(
+    session_id: &str,
+    content_id: &str,
+    content_type: &str,
+    tracks: &[TextTrack],
+) -> Value {
+    let ids: Vec<u32> = (TRACK_ID..).take(tracks.len()).collect();
+    json!({
+        "type": "LOAD",
+        "sessionId": session_id,
+        "autoplay": true,
+        "activeTrackIds": ids,
+        "media": {
+            "contentId": content_id,
+            "contentType": content_type,
+            "streamType": "BUFFERED",
+            "tracks": tracks
+                .iter()
+                .zip(&ids)
+                .map(|(track, id)| track.to_json(*id))
+                .collect::<Vec<_>>(),
+        },
+    })
+}
+
+fn media_session_id(status: &Value) -> Result<i64, Error> {
+    status["status"][0]["mediaSessionId"]
+        .as_i64()
+        .ok_or(Error::Protocol("media did not load"))
+}
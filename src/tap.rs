@@ -0,0 +1,48 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{device::Device, AsyncCastDevice, AsyncTransport, CastMessage, Connection, Direction};
+
+pub type MessageTap = Arc<dyn Fn(Direction, &CastMessage) + Send + Sync>;
+
+pub(crate) type TapSlot = Arc<Mutex<Option<MessageTap>>>;
+
+impl AsyncTransport {
+    pub fn set_message_tap(&mut self, tap: Option<MessageTap>) {
+        self.tap = tap;
+    }
+}
+
+impl AsyncCastDevice {
+    pub fn set_message_tap(&mut self, tap: Option<MessageTap>) {
+        self.transport.set_message_tap(tap);
+    }
+}
+
+impl Device {
+    pub(crate) async fn forward_to(&self, slot: &TapSlot) {
+        self.inner.lock().await.set_message_tap(Some(forward(slot)));
+    }
+}
+
+impl Connection {
+    pub fn set_message_tap<F>(&mut self, tap: F)
+    where
+        F: Fn(Direction, &CastMessage) + Send + Sync + 'static,
+    {
+        *self.link.tap.lock().unwrap() = Some(Arc::new(tap));
+    }
+
+    pub fn clear_message_tap(&mut self) {
+        self.link.tap.lock().unwrap().take();
+    }
+}
+
+pub(crate) fn forward(slot: &TapSlot) -> MessageTap {
+    let slot = slot.clone();
+    Arc::new(move |direction, message| {
+        let tap = slot.lock().unwrap().clone();
+        if let Some(tap) = tap {
+            tap(direction, message);
+        }
+    })
+}
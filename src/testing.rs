@@ -0,0 +1,306 @@
+//! An in-process Cast receiver that speaks the Cast v2 protocol over TLS on a loopback port.
+//! It does not answer mDNS queries, so discovery isn't covered: tests connect through
+//! [`MockCastDevice::target`] instead of `discover`.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use async_io::{block_on, Async};
+use async_native_tls::TlsAcceptor;
+use serde_json::{json, Value};
+
+use crate::{
+    AsyncTransport, CastMessage, Error, Payload, Target, DEFAULT_MEDIA_RECEIVER,
+    NAMESPACE_CONNECTION, NAMESPACE_HEARTBEAT, NAMESPACE_MEDIA, NAMESPACE_RECEIVER,
+};
+
+const TRANSPORT_ID: &str = "mock-transport-0";
+const SESSION_ID: &str = "mock-session-0";
+const DEFAULT_PLAYBACK: Duration = Duration::from_millis(200);
+
+struct Playback {
+    media_session_id: i32,
+    content_id: String,
+    content_type: String,
+    started: Instant,
+    stopped: bool,
+}
+
+struct State {
+    volume: f32,
+    muted: bool,
+    app: Option<String>,
+    media: Option<Playback>,
+    next_media_session: i32,
+    playback: Duration,
+    answer_pings: bool,
+    loaded: Vec<String>,
+    received: Vec<CastMessage>,
+}
+
+pub struct MockCastDevice {
+    name: String,
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl MockCastDevice {
+    pub fn start<T: Into<String>>(name: T) -> Result<Self, Error> {
+        let acceptor = Arc::new(TlsAcceptor::from(
+            native_tls::TlsAcceptor::new(identity()?)?,
+        ));
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            volume: 0.5,
+            muted: false,
+            app: None,
+            media: None,
+            next_media_session: 1,
+            playback: DEFAULT_PLAYBACK,
+            answer_pings: true,
+            loaded: Vec::new(),
+            received: Vec::new(),
+        }));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (serving, stopping) = (state.clone(), stopped.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopping.load(Ordering::SeqCst) {
+                    return;
+                }
+                if let Ok(stream) = stream {
+                    let (acceptor, state) = (acceptor.clone(), serving.clone());
+                    thread::spawn(move || block_on(serve(stream, &acceptor, &state)));
+                }
+            }
+        });
+        Ok(MockCastDevice {
+            name: name.into(),
+            addr,
+            state,
+            stopped,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn target(&self) -> Target {
+        Target::new(self.name.clone(), self.addr)
+    }
+
+    pub fn set_playback_duration(&self, duration: Duration) {
+        self.state.lock().unwrap().playback = duration;
+    }
+
+    pub fn set_answer_pings(&self, answer: bool) {
+        self.state.lock().unwrap().answer_pings = answer;
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.state.lock().unwrap().volume
+    }
+
+    pub fn muted(&self) -> bool {
+        self.state.lock().unwrap().muted
+    }
+
+    pub fn running_app(&self) -> Option<String> {
+        self.state.lock().unwrap().app.clone()
+    }
+
+    pub fn loaded(&self) -> Vec<String> {
+        self.state.lock().unwrap().loaded.clone()
+    }
+
+    pub fn received(&self) -> Vec<CastMessage> {
+        self.state.lock().unwrap().received.clone()
+    }
+}
+
+impl Drop for MockCastDevice {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn identity() -> Result<native_tls::Identity, Error> {
+    let certificate = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()])
+        .map_err(|_| Error::Protocol("failed to generate mock certificate"))?;
+    let pem = certificate
+        .serialize_pem()
+        .map_err(|_| Error::Protocol("failed to generate mock certificate"))?;
+    let key = certificate.serialize_private_key_pem();
+    Ok(native_tls::Identity::from_pkcs8(
+        pem.as_bytes(),
+        key.as_bytes(),
+    )?)
+}
+
+async fn serve(
+    stream: TcpStream,
+    acceptor: &TlsAcceptor,
+    state: &Mutex<State>,
+) -> Result<(), Error> {
+    let stream = acceptor.accept(Async::new(stream)?).await?;
+    let mut transport = AsyncTransport::from_stream(stream);
+    loop {
+        let message = transport.receive().await?;
+        let reply = state.lock().unwrap().handle(&message);
+        if let Some(reply) = reply {
+            transport.send(&reply).await?;
+        }
+    }
+}
+
+impl State {
+    fn handle(&mut self, message: &CastMessage) -> Option<CastMessage> {
+        self.received.push(message.clone());
+        let request = message.to_json()?;
+        let kind = request["type"].as_str().unwrap_or_default();
+        let mut payload = match (message.namespace.as_str(), kind) {
+            (NAMESPACE_CONNECTION, _) => return None,
+            (NAMESPACE_HEARTBEAT, "PING") if self.answer_pings => json!({ "type": "PONG" }),
+            (NAMESPACE_RECEIVER, "GET_STATUS") => self.receiver_status(),
+            (NAMESPACE_RECEIVER, "LAUNCH") => {
+                self.app = request["appId"].as_str().map(String::from);
+                self.media = None;
+                self.receiver_status()
+            }
+            (NAMESPACE_RECEIVER, "STOP") => {
+                self.app = None;
+                self.media = None;
+                self.receiver_status()
+            }
+            (NAMESPACE_RECEIVER, "SET_VOLUME") => {
+                if let Some(level) = request["volume"]["level"].as_f64() {
+                    self.volume = (level as f32).clamp(0., 1.);
+                }
+                if let Some(muted) = request["volume"]["muted"].as_bool() {
+                    self.muted = muted;
+                }
+                self.receiver_status()
+            }
+            (NAMESPACE_MEDIA, "LOAD") => {
+                let content_id = request["media"]["contentId"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_owned();
+                self.loaded.push(content_id.clone());
+                self.media = Some(Playback {
+                    media_session_id: self.next_media_session,
+                    content_id,
+                    content_type: request["media"]["contentType"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_owned(),
+                    started: Instant::now(),
+                    stopped: false,
+                });
+                self.next_media_session += 1;
+                self.media_status()
+            }
+            (NAMESPACE_MEDIA, "STOP") => {
+                if let Some(media) = &mut self.media {
+                    media.stopped = true;
+                }
+                self.media_status()
+            }
+            (NAMESPACE_MEDIA, _) => self.media_status(),
+            _ => return None,
+        };
+        if !request["requestId"].is_null() {
+            payload["requestId"] = request["requestId"].clone();
+        }
+        Some(CastMessage {
+            source: message.destination.clone(),
+            destination: message.source.clone(),
+            namespace: message.namespace.clone(),
+            payload: Payload::Text(payload.to_string()),
+        })
+    }
+
+    fn receiver_status(&self) -> Value {
+        let applications: Vec<Value> = self
+            .app
+            .iter()
+            .map(|app_id| {
+                let display_name = if app_id == DEFAULT_MEDIA_RECEIVER {
+                    "Default Media Receiver"
+                } else {
+                    app_id
+                };
+                json!({
+                    "appId": app_id,
+                    "displayName": display_name,
+                    "isIdleScreen": false,
+                    "namespaces": [{ "name": NAMESPACE_MEDIA }],
+                    "sessionId": SESSION_ID,
+                    "statusText": "",
+                    "transportId": TRANSPORT_ID,
+                })
+            })
+            .collect();
+        json!({
+            "type": "RECEIVER_STATUS",
+            "status": {
+                "applications": applications,
+                "isActiveInput": true,
+                "isStandBy": false,
+                "volume": {
+                    "controlType": "attenuation",
+                    "level": self.volume,
+                    "muted": self.muted,
+                    "stepInterval": 0.05,
+                },
+            },
+        })
+    }
+
+    fn media_status(&self) -> Value {
+        let media = match &self.media {
+            Some(media) => media,
+            None => return json!({ "type": "MEDIA_STATUS", "status": [] }),
+        };
+        let elapsed = media.started.elapsed().min(self.playback);
+        let mut entry = json!({
+            "mediaSessionId": media.media_session_id,
+            "playbackRate": 1,
+            "playerState": "PLAYING",
+            "currentTime": elapsed.as_secs_f32(),
+            "supportedMediaCommands": 15,
+            "volume": { "level": 1, "muted": false },
+            "media": {
+                "contentId": media.content_id,
+                "contentType": media.content_type,
+                "streamType": "BUFFERED",
+                "duration": self.playback.as_secs_f32(),
+            },
+        });
+        if media.stopped || elapsed >= self.playback {
+            entry["playerState"] = "IDLE".into();
+            entry["idleReason"] = if media.stopped {
+                "CANCELLED"
+            } else {
+                "FINISHED"
+            }
+            .into();
+        }
+        json!({ "type": "MEDIA_STATUS", "status": [entry] })
+    }
+}
@@ -0,0 +1,338 @@
+use std::{collections::HashMap, sync::Arc};
+
+const UNITS: &[(&str, &str)] = &[
+    ("°C", " degrees Celsius"),
+    ("°F", " degrees Fahrenheit"),
+    ("°", " degrees"),
+    ("km/h", " kilometers per hour"),
+    ("mph", " miles per hour"),
+    ("kWh", " kilowatt hours"),
+    ("%", " percent"),
+];
+
+pub trait Preprocessor: Send + Sync {
+    fn process(&self, text: &str) -> String;
+}
+
+impl<F: Fn(&str) -> String + Send + Sync> Preprocessor for F {
+    fn process(&self, text: &str) -> String {
+        self(text)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Lexicon {
+    words: HashMap<String, String>,
+}
+
+impl Lexicon {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pronounce<T: AsRef<str>, U: Into<String>>(mut self, word: T, spoken: U) -> Self {
+        self.words
+            .insert(word.as_ref().to_lowercase(), spoken.into());
+        self
+    }
+}
+
+impl Preprocessor for Lexicon {
+    fn process(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(is_word) {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest.find(|c| !is_word(c)).unwrap_or(rest.len());
+            let word = &rest[..end];
+            match self.words.get(&word.to_lowercase()) {
+                Some(spoken) => out.push_str(spoken),
+                None => out.push_str(word),
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Arc<dyn Preprocessor>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then<P: Preprocessor + 'static>(mut self, stage: P) -> Self {
+        self.stages.push(Arc::new(stage));
+        self
+    }
+
+    pub(crate) fn push(&mut self, stage: Arc<dyn Preprocessor>) {
+        self.stages.push(stage);
+    }
+}
+
+impl Preprocessor for Pipeline {
+    fn process(&self, text: &str) -> String {
+        self.stages
+            .iter()
+            .fold(text.to_owned(), |text, stage| stage.process(&text))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExpandUnits;
+
+impl Preprocessor for ExpandUnits {
+    fn process(&self, text: &str) -> String {
+        let mut out = text.to_owned();
+        for (unit, spoken) in UNITS {
+            let mut expanded = String::with_capacity(out.len());
+            let mut rest = out.as_str();
+            while let Some(start) = rest.find(unit) {
+                let before = &rest[..start];
+                let after = &rest[start + unit.len()..];
+                let bounded = !after.starts_with(|c: char| c.is_alphanumeric());
+                expanded.push_str(before);
+                if bounded && before.ends_with(|c: char| c.is_ascii_digit() || c == ' ') {
+                    expanded.push_str(spoken);
+                } else {
+                    expanded.push_str(unit);
+                }
+                rest = after;
+            }
+            expanded.push_str(rest);
+            out = expanded;
+        }
+        collapse_whitespace(&out)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripEmoji;
+
+impl Preprocessor for StripEmoji {
+    fn process(&self, text: &str) -> String {
+        collapse_whitespace(&text.chars().filter(|c| !is_emoji(*c)).collect::<String>())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpellAcronyms;
+
+impl Preprocessor for SpellAcronyms {
+    fn process(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(is_word) {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest.find(|c| !is_word(c)).unwrap_or(rest.len());
+            let word = &rest[..end];
+            if (2..=5).contains(&word.len()) && word.chars().all(|c| c.is_ascii_uppercase()) {
+                out.push_str(&word.chars().map(String::from).collect::<Vec<_>>().join(" "));
+            } else {
+                out.push_str(word);
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0xFE0F | 0x200D | 0x20E3
+    )
+}
+
+/// Keeps line breaks, which the chunker treats as sentence boundaries.
+fn collapse_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '\'' || c == '&'
+}
+
+pub(crate) fn strip_ssml(ssml: &str) -> String {
+    let mut out = String::with_capacity(ssml.len());
+    let mut rest = ssml;
+    let mut skip_until = None;
+    while let Some(open) = rest.find('<') {
+        if skip_until.is_none() {
+            out.push_str(&unescape(&rest[..open]));
+        }
+        rest = &rest[open + 1..];
+        let close = match rest.find('>') {
+            Some(close) => close,
+            None => {
+                rest = "";
+                break;
+            }
+        };
+        let tag = rest[..close].trim();
+        rest = &rest[close + 1..];
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        match skip_until {
+            Some(end) if tag == end => skip_until = None,
+            Some(_) => {}
+            None if name == "sub" && !tag.starts_with('/') => {
+                if let Some(alias) = attribute(tag, "alias") {
+                    out.push_str(&unescape(alias));
+                    skip_until = Some("/sub");
+                }
+            }
+            None if matches!(name, "break" | "p" | "s") => out.push(' '),
+            None => {}
+        }
+    }
+    if skip_until.is_none() {
+        out.push_str(&unescape(rest));
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!("{}=", name))? + name.len() + 1;
+    let value = &tag[start..];
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    Some(&value[..value.find(quote)?])
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexicon_replaces_whole_words_ignoring_case() {
+        let lexicon = Lexicon::new().pronounce("GIF", "jif");
+        assert_eq!(lexicon.process("The gif, or GIF."), "The jif, or jif.");
+        assert_eq!(lexicon.process("gifts"), "gifts");
+    }
+
+    #[test]
+    fn expand_units_after_numbers() {
+        assert_eq!(
+            ExpandUnits.process("It is 21°C outside"),
+            "It is 21 degrees Celsius outside"
+        );
+        assert_eq!(
+            ExpandUnits.process("Battery at 80%"),
+            "Battery at 80 percent"
+        );
+        assert_eq!(
+            ExpandUnits.process("Limit 90 km/h"),
+            "Limit 90 kilometers per hour"
+        );
+    }
+
+    #[test]
+    fn expand_units_leaves_words_alone() {
+        assert_eq!(ExpandUnits.process("50 mphs"), "50 mphs");
+        assert_eq!(ExpandUnits.process("triumph"), "triumph");
+    }
+
+    #[test]
+    fn spell_acronyms_between_two_and_five_letters() {
+        assert_eq!(
+            SpellAcronyms.process("The NASA API is OK"),
+            "The N A S A A P I is O K"
+        );
+        assert_eq!(SpellAcronyms.process("A LONGWORD"), "A LONGWORD");
+    }
+
+    #[test]
+    fn pipeline_runs_stages_in_order() {
+        let pipeline = Pipeline::new()
+            .then(|text: &str| text.to_uppercase())
+            .then(SpellAcronyms);
+        assert_eq!(pipeline.process("ups"), "U P S");
+        let pipeline = Pipeline::new()
+            .then(Lexicon::new().pronounce("temp", "temperature"))
+            .then(ExpandUnits);
+        assert_eq!(
+            pipeline.process("temp 20°C"),
+            "temperature 20 degrees Celsius"
+        );
+        assert_eq!(Pipeline::new().process("unchanged"), "unchanged");
+    }
+
+    #[test]
+    fn collapsing_whitespace_keeps_line_breaks() {
+        assert_eq!(
+            ExpandUnits.process("It is  21°C\nBattery at 80%"),
+            "It is 21 degrees Celsius\nBattery at 80 percent"
+        );
+        assert_eq!(
+            StripEmoji.process("Door open 🚪\r\n\tLights  off 💡"),
+            "Door open\nLights off"
+        );
+    }
+
+    #[test]
+    fn strip_emoji_removes_modifiers_and_joiners() {
+        assert_eq!(StripEmoji.process("🔔 Ding 👍🏽 👨‍👩‍👧 done ✔️"), "Ding done");
+    }
+
+    #[test]
+    fn strip_ssml_keeps_text_and_drops_tags() {
+        assert_eq!(
+            strip_ssml("<speak>Hello <emphasis level=\"strong\">world</emphasis></speak>"),
+            "Hello world"
+        );
+        assert_eq!(
+            strip_ssml("<speak>One<break time=\"1s\"/>two<p>three</p><s>four</s></speak>"),
+            "One two three four"
+        );
+    }
+
+    #[test]
+    fn strip_ssml_speaks_sub_aliases() {
+        assert_eq!(
+            strip_ssml("<sub alias=\"World Wide Web\">WWW</sub> is up"),
+            "World Wide Web is up"
+        );
+        assert_eq!(strip_ssml("<sub alias='two'>2</sub>"), "two");
+        assert_eq!(strip_ssml("<sub>H2O</sub>"), "H2O");
+    }
+
+    #[test]
+    fn strip_ssml_unescapes_entities_once() {
+        assert_eq!(
+            strip_ssml("<speak>Tom &amp; Jerry &lt;3 &quot;hi&quot;</speak>"),
+            "Tom & Jerry <3 \"hi\""
+        );
+        assert_eq!(strip_ssml("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn strip_ssml_drops_an_unterminated_tag() {
+        assert_eq!(strip_ssml("Hello <break"), "Hello");
+        assert_eq!(strip_ssml("plain text"), "plain text");
+        assert_eq!(strip_ssml(""), "");
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{ChimeSource, Melody, MixMode, Priority, SayOptions};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub chime: Option<ChimeSource>,
+    pub voice: Option<String>,
+    pub volume: Option<f32>,
+    pub priority: Option<Priority>,
+    pub mix: Option<MixMode>,
+}
+
+impl Theme {
+    pub fn new<T: Into<String>>(name: T) -> Self {
+        Theme {
+            name: name.into(),
+            chime: None,
+            voice: None,
+            volume: None,
+            priority: None,
+            mix: None,
+        }
+    }
+
+    pub fn urgent() -> Self {
+        Theme::new("urgent")
+            .chime(ChimeSource::Melody(Melody::alert()))
+            .volume(1.)
+            .priority(Priority::Urgent)
+    }
+
+    pub fn gentle() -> Self {
+        Theme::new("gentle")
+            .chime(ChimeSource::Melody(Melody::gentle()))
+            .volume(0.35)
+            .priority(Priority::Low)
+            .mix(MixMode::Interrupt)
+    }
+
+    pub fn doorbell() -> Self {
+        Theme::new("doorbell")
+            .chime(ChimeSource::Melody(Melody::doorbell()))
+            .volume(0.7)
+            .priority(Priority::High)
+    }
+
+    pub fn chime(mut self, source: ChimeSource) -> Self {
+        self.chime = Some(source);
+        self
+    }
+
+    pub fn voice<T: Into<String>>(mut self, voice: T) -> Self {
+        self.voice = Some(voice.into());
+        self
+    }
+
+    pub fn volume(mut self, level: f32) -> Self {
+        self.volume = Some(level.clamp(0., 1.));
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn mix(mut self, mode: MixMode) -> Self {
+        self.mix = Some(mode);
+        self
+    }
+
+    pub(crate) fn apply(&self, options: &mut SayOptions) {
+        if let Some(chime) = &self.chime {
+            options.chime = Some(chime.clone());
+        }
+        if let Some(voice) = &self.voice {
+            options.voice = Some(voice.clone());
+        }
+        if let Some(volume) = self.volume {
+            options.volume = Some(volume);
+        }
+        if let Some(priority) = self.priority {
+            options.priority = priority;
+        }
+        if let Some(mix) = self.mix {
+            options.mix = mix;
+        }
+    }
+}
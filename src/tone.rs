@@ -0,0 +1,161 @@
+use std::{f32::consts::PI, time::Duration};
+
+use crate::chime::wav;
+
+const SAMPLE_RATE: u32 = 22050;
+const AMPLITUDE: f32 = 0.6;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Note {
+    pub frequencies: Vec<u32>,
+    pub duration: Duration,
+}
+
+impl Note {
+    pub fn tone(frequency: u32, duration: Duration) -> Self {
+        Self::chord([frequency], duration)
+    }
+
+    pub fn chord<I: IntoIterator<Item = u32>>(frequencies: I, duration: Duration) -> Self {
+        Note {
+            frequencies: frequencies.into_iter().collect(),
+            duration,
+        }
+    }
+
+    pub fn rest(duration: Duration) -> Self {
+        Self::chord([], duration)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Melody {
+    notes: Vec<Note>,
+}
+
+impl Melody {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn chime() -> Self {
+        Melody::new()
+            .note(Note::tone(880, Duration::from_millis(180)))
+            .note(Note::tone(660, Duration::from_millis(320)))
+    }
+
+    pub fn doorbell() -> Self {
+        Melody::new()
+            .note(Note::tone(659, Duration::from_millis(400)))
+            .note(Note::tone(523, Duration::from_millis(600)))
+    }
+
+    pub fn alert() -> Self {
+        let beep = Note::tone(988, Duration::from_millis(120));
+        let gap = Note::rest(Duration::from_millis(60));
+        Melody::new()
+            .note(beep.clone())
+            .note(gap.clone())
+            .note(beep.clone())
+            .note(gap)
+            .note(beep)
+    }
+
+    pub fn gentle() -> Self {
+        Melody::new().note(Note::chord([523, 659, 784], Duration::from_millis(700)))
+    }
+
+    pub fn note(mut self, note: Note) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.notes.iter().map(|note| note.duration).sum()
+    }
+
+    pub fn to_wav(&self) -> Vec<u8> {
+        let mut samples = Vec::new();
+        for note in &self.notes {
+            let count = (SAMPLE_RATE as f32 * note.duration.as_secs_f32()) as usize;
+            let gain = AMPLITUDE / note.frequencies.len().max(1) as f32;
+            for i in 0..count {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let envelope = (1. - i as f32 / count as f32).powi(2);
+                let value: f32 = note
+                    .frequencies
+                    .iter()
+                    .map(|&frequency| (2. * PI * frequency as f32 * t).sin())
+                    .sum();
+                samples.push((value * envelope * gain * f32::from(i16::MAX)) as i16);
+            }
+        }
+        wav(SAMPLE_RATE, &samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(wav: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes([
+            wav[offset],
+            wav[offset + 1],
+            wav[offset + 2],
+            wav[offset + 3],
+        ])
+    }
+
+    fn samples(wav: &[u8]) -> Vec<i16> {
+        wav[44..]
+            .chunks(2)
+            .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn empty_melody_is_a_bare_header() {
+        let wav = Melody::new().to_wav();
+        assert_eq!(wav.len(), 44);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32_at(&wav, 4), 36);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(&wav, 24), SAMPLE_RATE);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32_at(&wav, 40), 0);
+    }
+
+    #[test]
+    fn wav_length_follows_note_durations() {
+        let melody = Melody::new()
+            .note(Note::tone(440, Duration::from_millis(500)))
+            .note(Note::rest(Duration::from_millis(500)));
+        assert_eq!(melody.duration(), Duration::from_secs(1));
+        let wav = melody.to_wav();
+        let data = SAMPLE_RATE * 2;
+        assert_eq!(wav.len(), 44 + data as usize);
+        assert_eq!(u32_at(&wav, 4), 36 + data);
+        assert_eq!(u32_at(&wav, 40), data);
+    }
+
+    #[test]
+    fn samples_stay_within_amplitude() {
+        let melody = Melody::new()
+            .note(Note::chord([523, 659, 784], Duration::from_millis(500)))
+            .note(Note::rest(Duration::from_millis(500)));
+        let samples = samples(&melody.to_wav());
+        let peak = (AMPLITUDE * f32::from(i16::MAX)) as i16;
+        assert!(samples.iter().all(|sample| sample.abs() <= peak));
+        assert!(samples[..SAMPLE_RATE as usize / 2]
+            .iter()
+            .any(|sample| *sample != 0));
+        assert!(samples[SAMPLE_RATE as usize / 2..]
+            .iter()
+            .all(|sample| *sample == 0));
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::Error;
+
+const NATIVE_CONTENT_TYPES: &[&str] = &[
+    "audio/mpeg",
+    "audio/mp3",
+    "audio/aac",
+    "audio/mp4",
+    "audio/wav",
+    "audio/x-wav",
+    "audio/flac",
+    "audio/webm",
+];
+
+pub trait Transcoder: Send + Sync {
+    fn transcode(&self, bytes: Vec<u8>, content_type: &str) -> Result<(Vec<u8>, String), Error>;
+}
+
+impl<F> Transcoder for F
+where
+    F: Fn(Vec<u8>, &str) -> Result<(Vec<u8>, String), Error> + Send + Sync,
+{
+    fn transcode(&self, bytes: Vec<u8>, content_type: &str) -> Result<(Vec<u8>, String), Error> {
+        self(bytes, content_type)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Ffmpeg {
+    program: PathBuf,
+}
+
+impl Default for Ffmpeg {
+    fn default() -> Self {
+        Ffmpeg {
+            program: "ffmpeg".into(),
+        }
+    }
+}
+
+impl Ffmpeg {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_program<P: Into<PathBuf>>(program: P) -> Self {
+        Ffmpeg {
+            program: program.into(),
+        }
+    }
+}
+
+impl Transcoder for Ffmpeg {
+    fn transcode(&self, bytes: Vec<u8>, _content_type: &str) -> Result<(Vec<u8>, String), Error> {
+        let mut child = Command::new(&self.program)
+            .args(["-hide_banner", "-loglevel", "error", "-i", "pipe:0"])
+            .args(["-vn", "-codec:a", "libmp3lame", "-f", "mp3", "pipe:1"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let writer = thread::spawn(move || stdin.write_all(&bytes));
+        let output = child.wait_with_output()?;
+        let _ = writer.join();
+        if !output.status.success() {
+            return Err(Error::Transcode(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok((output.stdout, "audio/mpeg".into()))
+    }
+}
+
+pub(crate) fn is_native(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or("").trim();
+    NATIVE_CONTENT_TYPES
+        .iter()
+        .any(|native| native.eq_ignore_ascii_case(essence))
+}
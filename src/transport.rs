@@ -0,0 +1,508 @@
+use std::{
+    net::{Shutdown, SocketAddr, TcpStream},
+    time::Duration,
+};
+
+use async_io::{Async, Timer};
+use async_native_tls::{TlsConnector, TlsStream};
+use futures::{
+    future::{select, Either},
+    pin_mut, AsyncRead, AsyncReadExt, AsyncWriteExt,
+};
+use serde_json::{json, Value};
+
+#[cfg(feature = "message-tap")]
+use crate::MessageTap;
+use crate::{Error, DEFAULT_DESTINATION_ID, DEFAULT_MEDIA_RECEIVER};
+
+const SOURCE_ID: &str = "sender-0";
+const MAX_MESSAGE_LEN: usize = 64 * 1024;
+const PONG_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub const NAMESPACE_CONNECTION: &str = "urn:x-cast:com.google.cast.tp.connection";
+pub const NAMESPACE_HEARTBEAT: &str = "urn:x-cast:com.google.cast.tp.heartbeat";
+pub const NAMESPACE_RECEIVER: &str = "urn:x-cast:com.google.cast.receiver";
+pub const NAMESPACE_MEDIA: &str = "urn:x-cast:com.google.cast.media";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Payload {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CastMessage {
+    pub source: String,
+    pub destination: String,
+    pub namespace: String,
+    pub payload: Payload,
+}
+
+impl CastMessage {
+    pub fn json<T: Into<String>, U: Into<String>>(
+        destination: T,
+        namespace: U,
+        payload: &Value,
+    ) -> Self {
+        CastMessage {
+            source: SOURCE_ID.into(),
+            destination: destination.into(),
+            namespace: namespace.into(),
+            payload: Payload::Text(payload.to_string()),
+        }
+    }
+
+    pub fn to_json(&self) -> Option<Value> {
+        match &self.payload {
+            Payload::Text(text) => serde_json::from_str(text).ok(),
+            Payload::Binary(_) => None,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&[0x08, 0x00]);
+        encode_bytes(&mut out, 2, self.source.as_bytes());
+        encode_bytes(&mut out, 3, self.destination.as_bytes());
+        encode_bytes(&mut out, 4, self.namespace.as_bytes());
+        match &self.payload {
+            Payload::Text(text) => {
+                out.extend_from_slice(&[0x28, 0x00]);
+                encode_bytes(&mut out, 6, text.as_bytes());
+            }
+            Payload::Binary(data) => {
+                out.extend_from_slice(&[0x28, 0x01]);
+                encode_bytes(&mut out, 7, data);
+            }
+        }
+        out
+    }
+
+    fn decode(mut data: &[u8]) -> Option<Self> {
+        let mut message = CastMessage {
+            source: String::new(),
+            destination: String::new(),
+            namespace: String::new(),
+            payload: Payload::Binary(Vec::new()),
+        };
+        while !data.is_empty() {
+            let key = decode_varint(&mut data)?;
+            match key & 7 {
+                0 => {
+                    decode_varint(&mut data)?;
+                }
+                2 => {
+                    let len = decode_varint(&mut data)? as usize;
+                    if len > data.len() {
+                        return None;
+                    }
+                    let (field, rest) = data.split_at(len);
+                    data = rest;
+                    let text = || String::from_utf8(field.to_vec()).ok();
+                    match key >> 3 {
+                        2 => message.source = text()?,
+                        3 => message.destination = text()?,
+                        4 => message.namespace = text()?,
+                        6 => message.payload = Payload::Text(text()?),
+                        7 => message.payload = Payload::Binary(field.to_vec()),
+                        _ => {}
+                    }
+                }
+                _ => return None,
+            }
+        }
+        Some(message)
+    }
+}
+
+fn encode_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn encode_bytes(out: &mut Vec<u8>, field: u64, data: &[u8]) {
+    encode_varint(out, field << 3 | 2);
+    encode_varint(out, data.len() as u64);
+    out.extend_from_slice(data);
+}
+
+fn decode_varint(data: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = data.split_first()?;
+        *data = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn frame(message: &CastMessage) -> Vec<u8> {
+    let body = message.encode();
+    let mut out = Vec::with_capacity(4 + body.len());
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend_from_slice(&body);
+    out
+}
+
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<CastMessage, Error> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(Error::Protocol("oversized message"));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).await?;
+    CastMessage::decode(&body).ok_or(Error::Protocol("malformed message"))
+}
+
+fn is_heartbeat(message: &CastMessage, kind: &str) -> bool {
+    message.namespace == NAMESPACE_HEARTBEAT
+        && message.to_json().is_some_and(|body| body["type"] == kind)
+}
+
+pub struct AsyncTransport {
+    stream: TlsStream<Async<TcpStream>>,
+    #[cfg(feature = "message-tap")]
+    tap: Option<MessageTap>,
+}
+
+impl AsyncTransport {
+    pub async fn connect(addr: SocketAddr) -> Result<Self, Error> {
+        let connector = TlsConnector::new()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true);
+        Self::connect_with(addr, connector).await
+    }
+
+    pub async fn connect_with(addr: SocketAddr, connector: TlsConnector) -> Result<Self, Error> {
+        let tcp = Async::<TcpStream>::connect(addr).await?;
+        let stream = connector.connect(addr.ip().to_string(), tcp).await?;
+        Ok(Self::from_stream(stream))
+    }
+
+    pub(crate) fn socket(&self) -> Result<TcpStream, Error> {
+        Ok(self.stream.get_ref().get_ref().try_clone()?)
+    }
+
+    fn shutdown(&self) {
+        let _ = self.stream.get_ref().get_ref().shutdown(Shutdown::Both);
+    }
+
+    pub(crate) fn from_stream(stream: TlsStream<Async<TcpStream>>) -> Self {
+        AsyncTransport {
+            stream,
+            #[cfg(feature = "message-tap")]
+            tap: None,
+        }
+    }
+
+    pub async fn send(&mut self, message: &CastMessage) -> Result<(), Error> {
+        self.observe(Direction::Outbound, message);
+        self.stream.write_all(&frame(message)).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    pub async fn receive(&mut self) -> Result<CastMessage, Error> {
+        let message = read_frame(&mut self.stream).await?;
+        self.observe(Direction::Inbound, &message);
+        Ok(message)
+    }
+
+    fn observe(&self, _direction: Direction, _message: &CastMessage) {
+        log!(
+            trace,
+            target: "cast_notify::wire",
+            direction = ?_direction,
+            source = %_message.source,
+            destination = %_message.destination,
+            namespace = %_message.namespace,
+            payload = ?_message.payload,
+        );
+        #[cfg(feature = "message-tap")]
+        if let Some(tap) = &self.tap {
+            tap(_direction, _message);
+        }
+    }
+}
+
+pub struct AsyncCastDevice {
+    transport: AsyncTransport,
+    request_id: u64,
+}
+
+impl AsyncCastDevice {
+    pub async fn connect(addr: SocketAddr) -> Result<Self, Error> {
+        Self::from_transport(AsyncTransport::connect(addr).await?).await
+    }
+
+    pub async fn from_transport(transport: AsyncTransport) -> Result<Self, Error> {
+        let mut device = AsyncCastDevice {
+            transport,
+            request_id: 0,
+        };
+        device.connect_to(DEFAULT_DESTINATION_ID).await?;
+        device.ping().await?;
+        Ok(device)
+    }
+
+    pub async fn connect_to(&mut self, destination: &str) -> Result<(), Error> {
+        self.send(
+            destination,
+            NAMESPACE_CONNECTION,
+            json!({ "type": "CONNECT" }),
+        )
+        .await
+    }
+
+    /// Sends a PING and waits for the device's PONG. A device that does not answer in time
+    /// may have left a frame half read, so the socket is shut down rather than reused.
+    pub async fn ping(&mut self) -> Result<(), Error> {
+        self.send(
+            DEFAULT_DESTINATION_ID,
+            NAMESPACE_HEARTBEAT,
+            json!({ "type": "PING" }),
+        )
+        .await?;
+        let answered = {
+            let pong = async {
+                loop {
+                    if is_heartbeat(&self.receive().await?, "PONG") {
+                        return Ok(());
+                    }
+                }
+            };
+            pin_mut!(pong);
+            match select(pong, Timer::after(PONG_TIMEOUT)).await {
+                Either::Left((result, _)) => Some(result),
+                Either::Right(_) => None,
+            }
+        };
+        answered.unwrap_or_else(|| {
+            self.transport.shutdown();
+            Err(Error::Protocol("heartbeat was not answered"))
+        })
+    }
+
+    pub async fn send(
+        &mut self,
+        destination: &str,
+        namespace: &str,
+        payload: Value,
+    ) -> Result<(), Error> {
+        self.transport
+            .send(&CastMessage::json(destination, namespace, &payload))
+            .await
+    }
+
+    pub async fn request(
+        &mut self,
+        destination: &str,
+        namespace: &str,
+        mut payload: Value,
+    ) -> Result<Value, Error> {
+        self.request_id += 1;
+        let request_id = self.request_id;
+        payload["requestId"] = request_id.into();
+        self.send(destination, namespace, payload).await?;
+        loop {
+            let message = self.receive().await?;
+            if let Some(response) = message.to_json() {
+                if response["requestId"].as_u64() == Some(request_id) {
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    pub async fn receive(&mut self) -> Result<CastMessage, Error> {
+        loop {
+            let message = self.transport.receive().await?;
+            if !is_heartbeat(&message, "PING") {
+                return Ok(message);
+            }
+            let destination = message.source.clone();
+            self.send(&destination, NAMESPACE_HEARTBEAT, json!({ "type": "PONG" }))
+                .await?;
+        }
+    }
+
+    pub async fn launch_app(&mut self, app_id: &str) -> Result<(String, String), Error> {
+        let status = self
+            .request(
+                DEFAULT_DESTINATION_ID,
+                NAMESPACE_RECEIVER,
+                json!({ "type": "LAUNCH", "appId": app_id }),
+            )
+            .await?;
+        status["status"]["applications"]
+            .as_array()
+            .and_then(|apps| apps.iter().find(|app| app["appId"] == app_id))
+            .and_then(|app| {
+                Some((
+                    app["transportId"].as_str()?.to_string(),
+                    app["sessionId"].as_str()?.to_string(),
+                ))
+            })
+            .ok_or(Error::Protocol("app did not launch"))
+    }
+
+    pub async fn load(&mut self, content_id: &str, content_type: &str) -> Result<i64, Error> {
+        let (transport_id, session_id) = self.launch_app(DEFAULT_MEDIA_RECEIVER).await?;
+        self.connect_to(&transport_id).await?;
+        let status = self
+            .request(
+                &transport_id,
+                NAMESPACE_MEDIA,
+                json!({
+                    "type": "LOAD",
+                    "sessionId": session_id,
+                    "autoplay": true,
+                    "media": {
+                        "contentId": content_id,
+                        "contentType": content_type,
+                        "streamType": "BUFFERED",
+                    },
+                }),
+            )
+            .await?;
+        status["status"][0]["mediaSessionId"]
+            .as_i64()
+            .ok_or(Error::Protocol("media did not load"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn message(payload: Payload) -> CastMessage {
+        CastMessage {
+            source: SOURCE_ID.into(),
+            destination: "receiver-0".into(),
+            namespace: NAMESPACE_MEDIA.into(),
+            payload,
+        }
+    }
+
+    #[test]
+    fn varints_round_trip_across_byte_boundaries() {
+        let cases = [
+            (0, 1),
+            (0x7f, 1),
+            (0x80, 2),
+            (0x3fff, 2),
+            (0x4000, 3),
+            (u64::from(u32::MAX), 5),
+            (u64::MAX, 10),
+        ];
+        for (value, len) in cases {
+            let mut out = Vec::new();
+            encode_varint(&mut out, value);
+            assert_eq!(out.len(), len, "{:#x}", value);
+            let mut data = out.as_slice();
+            assert_eq!(decode_varint(&mut data), Some(value));
+            assert!(data.is_empty());
+        }
+    }
+
+    #[test]
+    fn unterminated_varint_is_rejected() {
+        assert_eq!(decode_varint(&mut &[0x80, 0x80][..]), None);
+        assert_eq!(decode_varint(&mut &[0xff; 11][..]), None);
+    }
+
+    #[test]
+    fn text_message_round_trips() {
+        let original = CastMessage::json(
+            "receiver-0",
+            NAMESPACE_RECEIVER,
+            &json!({ "type": "LAUNCH", "appId": "CC1AD845", "note": "Küche 🔔" }),
+        );
+        assert_eq!(
+            CastMessage::decode(&original.encode()),
+            Some(original.clone())
+        );
+        assert_eq!(original.to_json().unwrap()["note"], json!("Küche 🔔"));
+    }
+
+    #[test]
+    fn binary_payload_round_trips() {
+        let original = message(Payload::Binary((0..=255).cycle().take(300).collect()));
+        assert_eq!(CastMessage::decode(&original.encode()), Some(original));
+    }
+
+    #[test]
+    fn truncated_message_is_rejected() {
+        let encoded = message(Payload::Text("{}".repeat(100))).encode();
+        assert_eq!(CastMessage::decode(&encoded[..encoded.len() - 1]), None);
+        for end in 0..encoded.len() {
+            let _ = CastMessage::decode(&encoded[..end]);
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_in_a_string_field_is_rejected() {
+        let mut encoded = Vec::new();
+        encode_bytes(&mut encoded, 2, &[0xff, 0xfe]);
+        assert_eq!(CastMessage::decode(&encoded), None);
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let original = message(Payload::Text(json!({ "type": "PING" }).to_string()));
+        let framed = frame(&original);
+        assert_eq!(
+            u32::from_be_bytes([framed[0], framed[1], framed[2], framed[3]]) as usize,
+            framed.len() - 4
+        );
+        assert_eq!(
+            block_on(read_frame(&mut framed.as_slice())).unwrap(),
+            original
+        );
+    }
+
+    #[test]
+    fn truncated_frame_errors_instead_of_panicking() {
+        let framed = frame(&message(Payload::Binary(vec![7; 200])));
+        for end in 0..framed.len() {
+            assert!(matches!(
+                block_on(read_frame(&mut &framed[..end])),
+                Err(Error::Io(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn oversized_frame_is_rejected_before_reading_the_body() {
+        let len = (MAX_MESSAGE_LEN as u32 + 1).to_be_bytes();
+        assert!(matches!(
+            block_on(read_frame(&mut &len[..])),
+            Err(Error::Protocol("oversized message"))
+        ));
+    }
+
+    #[test]
+    fn malformed_frame_body_is_rejected() {
+        let framed = [0, 0, 0, 2, 0x0b, 0x00];
+        assert!(matches!(
+            block_on(read_frame(&mut &framed[..])),
+            Err(Error::Protocol("malformed message"))
+        ));
+    }
+}
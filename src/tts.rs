@@ -0,0 +1,275 @@
+use std::sync::Arc;
+
+use google_translate_tts::url;
+use rust_cast::channels::media::{Media, Metadata, StreamType};
+
+use crate::{ChimeSource, Error, MixMode, Preemption, Priority, ResumePolicy, Theme};
+
+const GOOGLE_MAX_LEN: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clip {
+    pub url: String,
+    pub content_type: String,
+}
+
+impl Clip {
+    pub(crate) fn media(&self) -> Media {
+        Media {
+            stream_type: StreamType::Buffered,
+            duration: None,
+            metadata: None,
+            content_type: self.content_type.clone(),
+            content_id: self.url.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SayOptions {
+    pub lang: Option<String>,
+    pub slow: bool,
+    pub ssml: bool,
+    pub volume: Option<f32>,
+    pub resume: Option<ResumePolicy>,
+    pub priority: Priority,
+    pub preemption: Preemption,
+    pub chime: Option<ChimeSource>,
+    pub metadata: Option<Metadata>,
+    pub mix: MixMode,
+    pub voice: Option<String>,
+}
+
+impl SayOptions {
+    pub fn chime(mut self, source: ChimeSource) -> Self {
+        self.chime = Some(source);
+        self
+    }
+
+    pub fn metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    pub fn mix(mut self, mode: MixMode) -> Self {
+        self.mix = mode;
+        self
+    }
+
+    pub fn preemption(mut self, mode: Preemption) -> Self {
+        self.preemption = mode;
+        self
+    }
+
+    pub fn voice<T: Into<String>>(mut self, voice: T) -> Self {
+        self.voice = Some(voice.into());
+        self
+    }
+
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        theme.apply(&mut self);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gender {
+    Female,
+    Male,
+    Neutral,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Voice {
+    pub id: String,
+    pub language: String,
+    pub gender: Option<Gender>,
+}
+
+pub trait TtsProvider: Send + Sync {
+    fn synthesize(
+        &self,
+        message: &str,
+        language: &str,
+        options: &SayOptions,
+    ) -> Result<Clip, Error>;
+
+    fn max_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn supports_ssml(&self) -> bool {
+        false
+    }
+
+    fn voices(&self) -> Vec<Voice> {
+        Vec::new()
+    }
+}
+
+impl<P: TtsProvider + ?Sized> TtsProvider for Arc<P> {
+    fn synthesize(
+        &self,
+        message: &str,
+        language: &str,
+        options: &SayOptions,
+    ) -> Result<Clip, Error> {
+        (**self).synthesize(message, language, options)
+    }
+
+    fn max_len(&self) -> Option<usize> {
+        (**self).max_len()
+    }
+
+    fn supports_ssml(&self) -> bool {
+        (**self).supports_ssml()
+    }
+
+    fn voices(&self) -> Vec<Voice> {
+        (**self).voices()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GoogleTranslate;
+
+impl TtsProvider for GoogleTranslate {
+    fn synthesize(
+        &self,
+        message: &str,
+        language: &str,
+        options: &SayOptions,
+    ) -> Result<Clip, Error> {
+        let mut url = url(message, language);
+        if options.slow {
+            url.push_str("&ttsspeed=0.24");
+        }
+        Ok(Clip {
+            url,
+            content_type: "audio/mp3".into(),
+        })
+    }
+
+    fn max_len(&self) -> Option<usize> {
+        Some(GOOGLE_MAX_LEN)
+    }
+}
+
+pub(crate) fn chunks(message: &str, max: usize) -> Vec<String> {
+    let max = max.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for sentence in sentences(message) {
+        let pieces = if sentence.chars().count() <= max {
+            vec![sentence]
+        } else {
+            sentence
+                .split_whitespace()
+                .flat_map(|word| split_word(word, max))
+                .collect()
+        };
+        for piece in pieces {
+            if !current.is_empty() && current.chars().count() + 1 + piece.chars().count() > max {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(piece);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn sentences(message: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = message.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let boundary = match c {
+            '.' | '!' | '?' => !matches!(chars.peek(), Some((_, next)) if !next.is_whitespace()),
+            '\n' => true,
+            _ => false,
+        };
+        if boundary {
+            let end = i + c.len_utf8();
+            sentences.push(message[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(message[start..].trim());
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+}
+
+fn split_word(word: &str, max: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = word;
+    while let Some((at, _)) = rest.char_indices().nth(max) {
+        pieces.push(&rest[..at]);
+        rest = &rest[at..];
+    }
+    pieces.push(rest);
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_sentences_on_terminators_followed_by_whitespace() {
+        assert_eq!(
+            sentences("It is 3.5 degrees. Stay in!  Why?\nBecause"),
+            vec!["It is 3.5 degrees.", "Stay in!", "Why?", "Because"]
+        );
+        assert_eq!(sentences("Wait... what?"), vec!["Wait...", "what?"]);
+    }
+
+    #[test]
+    fn packs_whole_sentences_up_to_the_limit() {
+        assert_eq!(chunks("One. Two! Three?", 10), vec!["One. Two!", "Three?"]);
+        assert_eq!(
+            chunks("The kettle has boiled. Tea is ready.", 24),
+            vec!["The kettle has boiled.", "Tea is ready."]
+        );
+    }
+
+    #[test]
+    fn breaks_long_sentences_between_words() {
+        let message = "the quick brown fox jumps over the lazy dog";
+        let pieces = chunks(message, 12);
+        assert!(pieces.iter().all(|chunk| chunk.chars().count() <= 12));
+        assert_eq!(pieces.join(" "), message);
+    }
+
+    #[test]
+    fn splits_a_single_word_longer_than_the_limit() {
+        assert_eq!(split_word("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(chunks("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(chunks("ab", 0), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn splits_multibyte_text_on_character_boundaries() {
+        assert_eq!(split_word("ééééé", 2), vec!["éé", "éé", "é"]);
+        assert_eq!(
+            chunks("日本語のテキスト", 3),
+            vec!["日本語", "のテキ", "スト"]
+        );
+        let pieces = chunks("🔔🔔🔔 ding", 2);
+        assert_eq!(pieces, vec!["🔔🔔", "🔔", "di", "ng"]);
+    }
+
+    #[test]
+    fn empty_input_has_no_chunks() {
+        assert!(chunks("", 10).is_empty());
+        assert!(chunks("  \n \t ", 10).is_empty());
+        assert!(sentences("").is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+use std::borrow::Cow;
+
+use crate::{Connection, Error, SayOptions};
+
+impl Connection {
+    pub async fn volume(&self) -> Result<f32, Error> {
+        self.run(|device| async move { Ok(device.receiver_status().await?.volume.unwrap_or(0.)) })
+            .await
+    }
+
+    pub async fn set_volume(&self, level: f32) -> Result<(), Error> {
+        let level = level.clamp(0., 1.);
+        self.run(move |device| async move { device.set_volume(level).await })
+            .await
+    }
+
+    pub async fn say_at_volume<'a, T: Into<Cow<'a, str>>>(
+        &self,
+        message: T,
+        level: f32,
+    ) -> Result<(), Error> {
+        let options = SayOptions {
+            volume: Some(level.clamp(0., 1.)),
+            ..SayOptions::default()
+        };
+        self.speak(message, options, self.provider.clone(), true)
+            .await
+    }
+}
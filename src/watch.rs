@@ -0,0 +1,128 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use async_io::Timer;
+use futures::{
+    stream::{self, select},
+    Stream, StreamExt,
+};
+use mdns::RecordKind;
+
+use crate::{filter::normalize_uuid, parse, responses, DiscoverOptions, Error, Target};
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+pub enum DeviceEvent {
+    Added(Target),
+    Removed {
+        name: String,
+        addr: SocketAddr,
+    },
+    Renamed {
+        uuid: String,
+        old: String,
+        new: String,
+    },
+}
+
+struct Known {
+    name: String,
+    uuid: Option<String>,
+    addr: SocketAddr,
+    expires: Instant,
+}
+
+enum Input {
+    Response(Result<mdns::Response, Error>),
+    Sweep,
+}
+
+impl DiscoverOptions {
+    pub fn watch(self) -> impl Stream<Item = Result<DeviceEvent, Error>> {
+        let sweeps = stream::unfold((), |()| async {
+            Timer::after(SWEEP_INTERVAL).await;
+            Some((Input::Sweep, ()))
+        });
+        let mut known: HashMap<String, Known> = HashMap::new();
+        select(responses(&self).map(Input::Response), sweeps).flat_map(move |input| {
+            let mut events = Vec::new();
+            match input {
+                Input::Response(Ok(response)) => {
+                    for record in &response.answers {
+                        if let RecordKind::PTR(instance) = &record.kind {
+                            if record.ttl == 0 {
+                                events.extend(known.remove(instance).map(removed));
+                            }
+                        }
+                    }
+                    let instance = response.additional.iter().find_map(|record| {
+                        if let RecordKind::SRV { .. } = record.kind {
+                            Some((record.name.clone(), record.ttl))
+                        } else {
+                            None
+                        }
+                    });
+                    if let (Some((instance, ttl)), Some(target)) =
+                        (instance, parse(&response, &self))
+                    {
+                        let uuid = target.uuid.as_deref().map(normalize_uuid);
+                        let entry = Known {
+                            name: target.name.clone(),
+                            uuid: uuid.clone(),
+                            addr: target.addr,
+                            expires: Instant::now() + Duration::from_secs(ttl.into()),
+                        };
+                        if ttl > 0 {
+                            let previous = known.remove(&instance).or_else(|| {
+                                let stale = known
+                                    .iter()
+                                    .find(|(_, device)| uuid.is_some() && device.uuid == uuid)
+                                    .map(|(instance, _)| instance.clone())?;
+                                known.remove(&stale)
+                            });
+                            known.insert(instance, entry);
+                            match (previous, &target.uuid) {
+                                (None, _) => events.push(Ok(DeviceEvent::Added(target))),
+                                (Some(previous), Some(uuid)) if previous.name != target.name => {
+                                    events.push(Ok(DeviceEvent::Renamed {
+                                        uuid: uuid.clone(),
+                                        old: previous.name,
+                                        new: target.name,
+                                    }))
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                Input::Response(Err(e)) => events.push(Err(e)),
+                Input::Sweep => {
+                    let now = Instant::now();
+                    let expired: Vec<String> = known
+                        .iter()
+                        .filter(|(_, device)| device.expires <= now)
+                        .map(|(instance, _)| instance.clone())
+                        .collect();
+                    for instance in expired {
+                        events.extend(known.remove(&instance).map(removed));
+                    }
+                }
+            }
+            stream::iter(events)
+        })
+    }
+}
+
+pub fn watch() -> impl Stream<Item = Result<DeviceEvent, Error>> {
+    DiscoverOptions::default().watch()
+}
+
+fn removed(device: Known) -> Result<DeviceEvent, Error> {
+    Ok(DeviceEvent::Removed {
+        name: device.name,
+        addr: device.addr,
+    })
+}
@@ -0,0 +1,91 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures::{pin_mut, StreamExt};
+
+use crate::{discover_for, filter::normalize_uuid, Error, Target};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(transparent))]
+pub struct Zones {
+    zones: HashMap<String, Vec<String>>,
+}
+
+impl Zones {
+    pub fn new() -> Self {
+        Zones::default()
+    }
+
+    pub fn zone<T, I, M>(mut self, label: T, members: I) -> Self
+    where
+        T: Into<String>,
+        I: IntoIterator<Item = M>,
+        M: Into<String>,
+    {
+        self.insert(label, members);
+        self
+    }
+
+    pub fn insert<T, I, M>(&mut self, label: T, members: I)
+    where
+        T: Into<String>,
+        I: IntoIterator<Item = M>,
+        M: Into<String>,
+    {
+        let label = label.into();
+        self.zones
+            .retain(|key, _| !key.eq_ignore_ascii_case(&label));
+        self.zones
+            .insert(label, members.into_iter().map(Into::into).collect());
+    }
+
+    pub fn get(&self, label: &str) -> Option<&[String]> {
+        self.zones
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(label))
+            .map(|(_, members)| members.as_slice())
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.zones.keys().map(String::as_str)
+    }
+
+    pub async fn resolve(
+        &self,
+        label: &str,
+        timeout: Duration,
+    ) -> Result<(Vec<Target>, Vec<Error>), Error> {
+        let mut pending: Vec<&str> = self
+            .get(label)
+            .ok_or_else(|| Error::UnknownZone(label.to_string()))?
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let mut found = Vec::new();
+        let targets = discover_for(timeout);
+        pin_mut!(targets);
+        while !pending.is_empty() {
+            let target = match targets.next().await {
+                Some(target) => target?,
+                None => break,
+            };
+            if let Some(i) = pending.iter().position(|member| is_member(&target, member)) {
+                pending.swap_remove(i);
+                found.push(target);
+            }
+        }
+        let missing = pending
+            .into_iter()
+            .map(|member| Error::DeviceNotFound(member.to_string()))
+            .collect();
+        Ok((found, missing))
+    }
+}
+
+fn is_member(target: &Target, member: &str) -> bool {
+    target.name.eq_ignore_ascii_case(member)
+        || target
+            .uuid
+            .as_deref()
+            .is_some_and(|uuid| normalize_uuid(uuid) == normalize_uuid(member))
+}
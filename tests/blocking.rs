@@ -0,0 +1,66 @@
+#![cfg(feature = "testing")]
+
+use std::{sync::Arc, time::Duration};
+
+use cast_notify::{
+    blocking::Connection, testing::MockCastDevice, Clip, Error, PlayerState, SayOptions,
+    TtsProvider,
+};
+
+const CLIP_URL: &str = "http://127.0.0.1:8000/clip.mp3";
+
+struct FixedClip;
+
+impl TtsProvider for FixedClip {
+    fn synthesize(&self, _: &str, _: &str, _: &SayOptions) -> Result<Clip, Error> {
+        Ok(Clip {
+            url: CLIP_URL.to_owned(),
+            content_type: "audio/mpeg".to_owned(),
+        })
+    }
+}
+
+fn connect(device: &MockCastDevice) -> Connection {
+    let mut connection = Connection::connect(device.target()).unwrap();
+    connection.inner_mut().set_provider(Arc::new(FixedClip));
+    connection
+}
+
+#[test]
+fn says_through_the_blocking_wrapper() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    let connection = connect(&device);
+    connection.say("Door open").unwrap();
+    assert_eq!(device.loaded(), vec![CLIP_URL.to_owned()]);
+}
+
+#[test]
+fn waits_for_playback_through_the_blocking_wrapper() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    device.set_playback_duration(Duration::from_millis(50));
+    let connection = connect(&device);
+    connection.say_and_wait("Door open").unwrap();
+    let media = connection.media_status().unwrap().unwrap();
+    assert!(matches!(media.player_state, PlayerState::Idle));
+}
+
+#[test]
+fn sets_volume_through_the_blocking_wrapper() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    let connection = connect(&device);
+    connection.set_volume(0.2).unwrap();
+    assert!((connection.volume().unwrap() - 0.2).abs() < f32::EPSILON);
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn refuses_to_block_inside_a_tokio_runtime() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    let connection = connect(&device);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let result = runtime.block_on(async { connection.say("Door open") });
+    assert!(matches!(result, Err(Error::InsideRuntime)));
+    assert!(device.loaded().is_empty());
+}
@@ -0,0 +1,321 @@
+#![cfg(feature = "testing")]
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_io::{block_on, Timer};
+use cast_notify::{
+    testing::MockCastDevice, AsyncCastDevice, Clip, ConnectOptions, Connection, Error, Group,
+    LifecycleEvent, LifecycleStage, Notifier, Outcome, PlayerState, Pool, SayOptions, Target,
+    TtsProvider, NAMESPACE_MEDIA,
+};
+use futures::future::select;
+
+const CLIP_URL: &str = "http://127.0.0.1:8000/clip.mp3";
+const DEFAULT_MEDIA_RECEIVER: &str = "CC1AD845";
+
+struct FixedClip;
+
+impl TtsProvider for FixedClip {
+    fn synthesize(&self, _: &str, _: &str, _: &SayOptions) -> Result<Clip, Error> {
+        Ok(Clip {
+            url: CLIP_URL.to_owned(),
+            content_type: "audio/mpeg".to_owned(),
+        })
+    }
+}
+
+fn run<F: Future<Output = Result<(), Error>>>(test: F) {
+    block_on(test).unwrap();
+}
+
+fn record(connection: &mut Connection) -> Arc<Mutex<Vec<LifecycleEvent>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    connection.on_lifecycle(move |event| recorded.lock().unwrap().push(event.clone()));
+    events
+}
+
+fn stages(events: &Mutex<Vec<LifecycleEvent>>) -> Vec<LifecycleStage> {
+    events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|event| event.stage)
+        .collect()
+}
+
+#[test]
+fn reads_and_sets_volume() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    run(async {
+        let connection = device.target().connect().await?;
+        assert_eq!(connection.volume().await?, 0.5);
+        connection.set_volume(0.2).await?;
+        assert!((device.volume() - 0.2).abs() < f32::EPSILON);
+        assert!((connection.volume().await? - 0.2).abs() < f32::EPSILON);
+        Ok(())
+    });
+}
+
+#[test]
+fn reports_idle_status() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    run(async {
+        let status = device.target().connect().await?.status().await?;
+        assert!(status.app.is_none());
+        assert!(status.media.is_none());
+        assert_eq!(status.volume, Some(0.5));
+        assert!(!status.muted);
+        assert!(!status.standby);
+        Ok(())
+    });
+}
+
+#[test]
+fn loads_announcement_into_media_receiver() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    run(async {
+        let connection = device.target().connect().await?;
+        let handle = connection
+            .say_with("Door open", Arc::new(FixedClip))
+            .await?;
+        assert!(handle.media_session_id().is_some());
+        assert_eq!(
+            device.running_app().as_deref(),
+            Some(DEFAULT_MEDIA_RECEIVER)
+        );
+        assert_eq!(device.loaded(), vec![CLIP_URL.to_owned()]);
+        Ok(())
+    });
+}
+
+#[test]
+fn completes_once_playback_goes_idle() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    device.set_playback_duration(Duration::from_millis(50));
+    run(async {
+        let connection = device.target().connect().await?;
+        let handle = connection
+            .say_with("Door open", Arc::new(FixedClip))
+            .await?;
+        assert_eq!(handle.done().await?, Outcome::Completed);
+        let media = connection.status().await?.media.unwrap();
+        assert!(matches!(media.player_state, PlayerState::Idle));
+        Ok(())
+    });
+}
+
+#[test]
+fn cancel_stops_the_clip_that_is_playing() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    device.set_playback_duration(Duration::from_secs(60));
+    run(async {
+        let connection = device.target().connect().await?;
+        let handle = connection
+            .say_with("Door open", Arc::new(FixedClip))
+            .await?;
+        assert_eq!(device.loaded(), vec![CLIP_URL.to_owned()]);
+        assert_eq!(handle.cancel().await?, Outcome::Cancelled);
+        let stopped = device.received().iter().any(|message| {
+            message.namespace == NAMESPACE_MEDIA
+                && message.to_json().is_some_and(|body| body["type"] == "STOP")
+        });
+        assert!(stopped);
+        Ok(())
+    });
+}
+
+#[test]
+fn reports_lifecycle_through_to_finished() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    device.set_playback_duration(Duration::from_millis(50));
+    run(async {
+        let mut connection = device.target().connect().await?;
+        connection.set_provider(Arc::new(FixedClip));
+        let events = record(&mut connection);
+        connection.say_and_wait("Door open").await?;
+        assert_eq!(
+            stages(&events),
+            vec![
+                LifecycleStage::Queued,
+                LifecycleStage::Started,
+                LifecycleStage::Finished
+            ]
+        );
+        let events = events.lock().unwrap();
+        assert!(events.iter().all(|event| event.id == events[0].id));
+        Ok(())
+    });
+}
+
+#[test]
+fn reports_finished_without_holding_up_say() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    device.set_playback_duration(Duration::from_millis(300));
+    run(async {
+        let mut connection = device.target().connect().await?;
+        connection.set_provider(Arc::new(FixedClip));
+        let events = record(&mut connection);
+        connection.say("Door open").await?;
+        assert_eq!(
+            stages(&events),
+            vec![LifecycleStage::Queued, LifecycleStage::Started]
+        );
+        for _ in 0..50 {
+            if stages(&events).len() == 3 {
+                break;
+            }
+            Timer::after(Duration::from_millis(100)).await;
+        }
+        assert_eq!(stages(&events).last(), Some(&LifecycleStage::Finished));
+        Ok(())
+    });
+}
+
+#[test]
+fn reports_an_abandoned_announcement_as_failed() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    device.set_playback_duration(Duration::from_secs(60));
+    run(async {
+        let mut connection = device.target().connect().await?;
+        connection.set_provider(Arc::new(FixedClip));
+        let events = record(&mut connection);
+        let say = Box::pin(connection.say_and_wait("Door open"));
+        drop(select(say, Timer::after(Duration::from_millis(500))).await);
+        let stages = stages(&events);
+        assert_eq!(stages.first(), Some(&LifecycleStage::Queued));
+        assert_eq!(stages.last(), Some(&LifecycleStage::Failed));
+        let last = events.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(last.error.as_deref(), Some("operation was cancelled"));
+        Ok(())
+    });
+}
+
+#[test]
+fn ping_waits_for_the_pong() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    run(async {
+        let mut cast = AsyncCastDevice::connect(device.addr()).await?;
+        cast.ping().await?;
+        device.set_answer_pings(false);
+        assert!(matches!(cast.ping().await, Err(Error::Protocol(_))));
+        Ok(())
+    });
+}
+
+#[test]
+fn heartbeat_tracks_whether_the_device_answers() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    run(async {
+        let options = ConnectOptions::default().heartbeat(Duration::from_millis(100));
+        let connection = device.target().connect_with(options).await?;
+        Timer::after(Duration::from_millis(500)).await;
+        let health = connection.health();
+        assert!(health.connected);
+        assert!(health.last_heartbeat.is_some());
+        device.set_answer_pings(false);
+        for _ in 0..100 {
+            if !connection.health().connected {
+                break;
+            }
+            Timer::after(Duration::from_millis(100)).await;
+        }
+        assert!(!connection.health().connected);
+        Ok(())
+    });
+}
+
+#[test]
+fn pool_drops_a_connection_that_stops_answering() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    let pool = Pool::new().health_check_interval(Duration::ZERO);
+    run(async {
+        let first = pool.get(&device.target()).await?;
+        assert!(Arc::ptr_eq(&first, &pool.get(&device.target()).await?));
+        device.set_answer_pings(false);
+        assert!(pool.get(&device.target()).await.is_err());
+        assert!(!first.health().connected);
+        assert!(pool.is_empty());
+        device.set_answer_pings(true);
+        let second = pool.get(&device.target()).await?;
+        assert!(!Arc::ptr_eq(&first, &second));
+        Ok(())
+    });
+}
+
+#[test]
+fn group_connect_reports_every_target() {
+    let kitchen = MockCastDevice::start("Kitchen").unwrap();
+    let hall = MockCastDevice::start("Hall").unwrap();
+    let attic = Target::new("Attic", "127.0.0.1:1".parse().unwrap());
+    run(async {
+        let targets = vec![kitchen.target(), attic, hall.target()];
+        let (group, outcome) = Group::connect(targets, 4).await;
+        assert_eq!(group.connections().len(), 2);
+        assert_eq!(outcome.len(), 3);
+        assert_eq!(outcome.successes().count(), 2);
+        let failures: Vec<_> = outcome.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].0.contains("Attic"));
+        Ok(())
+    });
+}
+
+#[test]
+fn group_with_volume_restores_every_member() {
+    let kitchen = MockCastDevice::start("Kitchen").unwrap();
+    let hall = MockCastDevice::start("Hall").unwrap();
+    run(async {
+        let (group, _) = Group::connect(vec![kitchen.target(), hall.target()], 4).await;
+        let (result, outcome) = group
+            .with_volume(0.9, |_| async {
+                assert!((kitchen.volume() - 0.9).abs() < f32::EPSILON);
+                assert!((hall.volume() - 0.9).abs() < f32::EPSILON);
+                Ok(())
+            })
+            .await;
+        result?;
+        assert!(outcome.all_ok());
+        assert_eq!(outcome.len(), 2);
+        assert!((kitchen.volume() - 0.5).abs() < f32::EPSILON);
+        assert!((hall.volume() - 0.5).abs() < f32::EPSILON);
+        Ok(())
+    });
+}
+
+#[test]
+fn notifier_health_covers_pooled_zone_members() {
+    let kitchen = MockCastDevice::start("Kitchen").unwrap();
+    let hall = MockCastDevice::start("Hall").unwrap();
+    let pool = Arc::new(Pool::new());
+    run(async {
+        pool.get(&hall.target()).await?;
+        pool.get(&kitchen.target()).await?;
+        let notifier = Notifier::new(kitchen.target().connect().await?).with_pool(pool.clone());
+        let health = notifier.health();
+        let devices: Vec<_> = health
+            .devices
+            .iter()
+            .map(|device| device.device.as_str())
+            .collect();
+        assert_eq!(devices, vec!["Kitchen", "Hall"]);
+        assert!(health.is_healthy());
+        Ok(())
+    });
+}
+
+#[test]
+fn rejects_empty_messages() {
+    let device = MockCastDevice::start("Kitchen").unwrap();
+    run(async {
+        let connection = device.target().connect().await?;
+        let result = connection.say_with("  ", Arc::new(FixedClip)).await;
+        assert!(matches!(result, Err(Error::EmptyMessage)));
+        assert!(device.loaded().is_empty());
+        Ok(())
+    });
+}